@@ -0,0 +1,77 @@
+use std::path::PathBuf;
+
+use anyhow::{Result, bail};
+use clap::{Args, ValueEnum};
+
+use bookworm::convert::{EpubToMarkdown, EpubToPdf};
+use bookworm::epub::Epub;
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum ConvertFormat {
+    /// Portable Document Format, from an EPUB input
+    Pdf,
+    /// Markdown, from an EPUB input
+    Markdown,
+}
+
+impl ConvertFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            Self::Pdf => "pdf",
+            Self::Markdown => "md",
+        }
+    }
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct ConvertOpt {
+    /// Path to the file to convert
+    input: PathBuf,
+    /// Format to convert the file into
+    #[clap(long, value_enum)]
+    format: ConvertFormat,
+    /// Path to write the converted file to, defaults to the input path with
+    /// the extension of the format
+    #[clap(long, short)]
+    output: Option<PathBuf>,
+    /// Adds a table of contents linking every chapter (Markdown only)
+    #[clap(long)]
+    toc: bool,
+}
+
+impl ConvertOpt {
+    pub async fn exec(&self) -> Result<()> {
+        let is_epub = self
+            .input
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("epub"));
+        let output = self
+            .output
+            .clone()
+            .unwrap_or_else(|| self.input.with_extension(self.format.extension()));
+
+        if !is_epub {
+            bail!(
+                "Cannot convert '{}', only EPUB files are supported",
+                self.input.display()
+            );
+        }
+
+        let epub = Epub::open_async(&self.input).await?;
+
+        match self.format {
+            ConvertFormat::Pdf => EpubToPdf::new(&epub).write(&output).await?,
+            ConvertFormat::Markdown => {
+                EpubToMarkdown::new(&epub)
+                    .generate_toc(self.toc)
+                    .write(&output)
+                    .await?
+            }
+        }
+
+        println!("Converted: {}", output.display());
+
+        Ok(())
+    }
+}
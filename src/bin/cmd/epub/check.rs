@@ -0,0 +1,30 @@
+use std::path::PathBuf;
+
+use anyhow::{Result, bail};
+use clap::Args;
+
+use bookworm::epub::Epub;
+
+#[derive(Args, Clone, Debug)]
+pub struct CheckOpt {
+    /// Path to the (K)Epub file
+    path: PathBuf,
+}
+
+impl CheckOpt {
+    pub async fn exec(&self) -> Result<()> {
+        let epub = Epub::open_async(&self.path).await?;
+        let issues = epub.validate().await?;
+
+        if issues.is_empty() {
+            println!("No issues found");
+            return Ok(());
+        }
+
+        for issue in &issues {
+            println!("{}", issue);
+        }
+
+        bail!("Found {} issue(s) in {}", issues.len(), self.path.display())
+    }
+}
@@ -0,0 +1,35 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::Args;
+
+use bookworm::epub::{Epub, TextCount};
+
+#[derive(Args, Clone, Debug)]
+pub struct CountOpt {
+    /// Path to the (K)Epub file
+    path: PathBuf,
+}
+
+impl CountOpt {
+    pub async fn exec(&self) -> Result<()> {
+        let epub = Epub::open_async(&self.path).await?;
+        let mut total = TextCount::default();
+
+        for (href, text) in epub.spine_texts().await? {
+            let count = TextCount::of(&text);
+
+            println!(
+                "{}: {} words, {} characters",
+                href, count.words, count.characters
+            );
+
+            total += count;
+        }
+
+        println!("Words: {}", total.words);
+        println!("Characters: {}", total.characters);
+
+        Ok(())
+    }
+}
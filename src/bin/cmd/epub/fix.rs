@@ -0,0 +1,42 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::Args;
+
+use bookworm::epub::Epub;
+use bookworm::util::fs::write_replacing;
+
+#[derive(Args, Clone, Debug)]
+pub struct FixOpt {
+    /// Path to the (K)Epub file
+    path: PathBuf,
+    /// Path to write the fixed (K)Epub file to, defaults to overwriting it
+    #[clap(long, short)]
+    output: Option<PathBuf>,
+}
+
+impl FixOpt {
+    pub async fn exec(&self) -> Result<()> {
+        let epub = Epub::open_async(&self.path).await?;
+        let findings = epub.mimetype_findings().await?;
+
+        if findings.is_empty() {
+            println!("No fix needed: {}", self.path.display());
+            return Ok(());
+        }
+
+        let bytes = epub.normalize().await?;
+        let output = self.output.as_ref().unwrap_or(&self.path);
+
+        drop(epub);
+        write_replacing(output, &bytes)?;
+
+        for finding in findings {
+            println!("Fixed: {}", finding);
+        }
+
+        println!("Wrote: {}", output.display());
+
+        Ok(())
+    }
+}
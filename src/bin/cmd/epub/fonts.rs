@@ -0,0 +1,39 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::Args;
+
+use bookworm::epub::{Epub, FontObfuscation};
+
+#[derive(Args, Clone, Debug)]
+pub struct FontsOpt {
+    /// Path to the (K)Epub file
+    path: PathBuf,
+}
+
+impl FontsOpt {
+    pub async fn exec(&self) -> Result<()> {
+        let epub = Epub::open_async(&self.path).await?;
+        let fonts = epub.fonts().await?;
+
+        if fonts.is_empty() {
+            println!("No embedded fonts found");
+            return Ok(());
+        }
+
+        for font in fonts {
+            let obfuscation = match font.obfuscation {
+                Some(FontObfuscation::Idpf) => "IDPF",
+                Some(FontObfuscation::Adobe) => "Adobe",
+                None => "none",
+            };
+
+            println!(
+                "{} ({}, {} bytes, obfuscation: {})",
+                font.href, font.media_type, font.size, obfuscation
+            );
+        }
+
+        Ok(())
+    }
+}
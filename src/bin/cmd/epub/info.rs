@@ -1,31 +1,105 @@
-use std::fs::rename;
+use std::fs::{create_dir_all, rename};
 use std::path::PathBuf;
 
-use anyhow::Result;
-use clap::Args;
+use anyhow::{Result, bail};
+use clap::builder::RangedU64ValueParser;
+use clap::{Args, ValueEnum};
+use serde_json::{Value, json};
 
-use bookworm::epub::Epub;
+use bookworm::epub::{Creator, Epub, Metadata, is_valid_isbn};
+use bookworm::util::fs::{available_path, sanitize_file_name};
+
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+pub enum InfoFormat {
+    /// Human readable `Field: value` lines
+    #[default]
+    Text,
+    /// A single JSON object, with `null` for missing fields
+    Json,
+}
+
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+pub enum RenameConflict {
+    /// Fails without renaming
+    #[default]
+    Error,
+    /// Appends " (2)", " (3)" and so on to the new file name
+    Number,
+}
 
 #[derive(Args, Clone, Debug)]
 pub struct InfoOpt {
     /// Path to the (K)Epub file
     path: PathBuf,
-    /// Renames the (K)Epub file
+    /// Renames the (K)Epub file to "{title} - {author}"
     #[clap(long)]
     rename: bool,
+    /// Renames the (K)Epub file after a template instead, with the
+    /// `{title}`, `{author}`, `{author_sort}`, `{year}`, `{isbn}` and
+    /// `{language}` placeholders. `{author_sort}` is the first author as
+    /// sorted, e.g. "Doe, Jane". A `/` creates directories, e.g.
+    /// `{author_sort}/{title} ({year})`
+    #[clap(long, value_name = "PATTERN")]
+    rename_template: Option<String>,
+    /// Prints the new path without renaming the file
+    #[clap(long)]
+    dry_run: bool,
+    /// What to do when a file already exists at the new path
+    #[clap(long, value_enum, default_value_t)]
+    on_conflict: RenameConflict,
+    /// Prints the accessibility metadata declared by the publisher
+    #[clap(long)]
+    a11y: bool,
+    /// Output format, the JSON object includes the whole OPF metadata under
+    /// `metadata`
+    #[clap(long, value_enum, default_value_t)]
+    format: InfoFormat,
+    /// Separator between author names in the renamed file
+    #[clap(long, default_value = ", ")]
+    author_separator: String,
+    /// Maximum authors in the renamed file, appending " et al." beyond it
+    #[clap(long, value_name = "N", value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+    max_authors: Option<usize>,
+    /// Looks the ISBN up in OpenLibrary to fill in missing metadata
+    #[cfg(feature = "online")]
+    #[clap(long)]
+    enrich: bool,
 }
 
 impl InfoOpt {
     pub async fn exec(&self) -> Result<()> {
-        let epub = Epub::open(&self.path)?;
-        let content_opf = epub.content_opf();
+        let epub = Epub::open_async(&self.path).await?;
+        let content_opf = epub.content_opf().await?;
 
-        println!("Title: {}", content_opf.metadata.title);
-        println!("Author: {}", content_opf.metadata.creator);
-        println!("Language: {}", content_opf.metadata.language);
-        println!("Identifier: {}", content_opf.metadata.identifier);
+        for finding in epub.mimetype_findings().await? {
+            eprintln!("Warning: {}", finding);
+        }
 
-        if self.rename {
+        for finding in content_opf.manifest_findings() {
+            eprintln!("Warning: {}", finding);
+        }
+
+        #[cfg(feature = "online")]
+        let enrichment = if self.enrich {
+            enrichment(&epub).await?
+        } else {
+            None
+        };
+        #[cfg(not(feature = "online"))]
+        let enrichment: Option<Value> = None;
+
+        match self.format {
+            InfoFormat::Text => {
+                self.print_text(&epub).await?;
+
+                if let Some(enrichment) = &enrichment {
+                    print_enrichment(enrichment);
+                }
+            }
+            InfoFormat::Json => print_json(&epub, enrichment).await?,
+        }
+
+        if self.rename || self.rename_template.is_some() {
             let parent = self
                 .path
                 .parent()
@@ -35,18 +109,45 @@ impl InfoOpt {
                 .extension()
                 .and_then(|ext| ext.to_str())
                 .ok_or_else(|| anyhow::anyhow!("Failed to get file extension"))?;
-            let safe_title = content_opf
-                .metadata
-                .title
-                .replace("/", "-")
-                .replace("\\", "-");
-            let safe_creator = content_opf
-                .metadata
-                .creator
-                .replace("/", "-")
-                .replace("\\", "-");
-            let new_file_name = format!("{} - {}.{}", safe_title, safe_creator, extension);
-            let new_path = parent.join(new_file_name);
+            let file_stem = match &self.rename_template {
+                Some(template) => self.render_template(template, &epub).await?,
+                None => format!(
+                    "{} - {}",
+                    sanitize_file_name(&content_opf.metadata.title),
+                    sanitize_file_name(&self.authors(&content_opf.metadata))
+                ),
+            };
+            // Trailing dots (e.g. from " et al.") would double the extension dot
+            let new_file_name = format!("{}.{}", file_stem.trim_end_matches('.'), extension);
+            let mut new_path = parent.join(new_file_name);
+
+            if new_path == self.path {
+                println!("Already named: {}", new_path.display());
+                return Ok(());
+            }
+
+            if new_path.exists() {
+                match self.on_conflict {
+                    RenameConflict::Error => bail!(
+                        "Refusing to rename to '{}', the file already exists",
+                        new_path.display()
+                    ),
+                    RenameConflict::Number => new_path = available_path(&new_path),
+                }
+            }
+
+            if self.dry_run {
+                println!(
+                    "Would rename: {} -> {}",
+                    self.path.display(),
+                    new_path.display()
+                );
+                return Ok(());
+            }
+
+            if let Some(new_parent) = new_path.parent() {
+                create_dir_all(new_parent)?;
+            }
 
             rename(&self.path, &new_path)?;
 
@@ -55,4 +156,290 @@ impl InfoOpt {
 
         Ok(())
     }
+
+    /// Joins the author names for the renamed file, keeping at most
+    /// `--max-authors` of them. See [`Metadata::authors`].
+    fn authors(&self, metadata: &Metadata) -> String {
+        let creators = metadata.authors();
+        let max_authors = self.max_authors.unwrap_or(creators.len());
+        let mut authors = creators
+            .iter()
+            .take(max_authors)
+            .map(|creator| creator.name.as_str())
+            .collect::<Vec<&str>>()
+            .join(&self.author_separator);
+
+        if creators.len() > max_authors {
+            authors.push_str(" et al.");
+        }
+
+        authors
+    }
+
+    /// Substitutes the placeholders of a `--rename-template` with the book
+    /// metadata. Values go through [`sanitize_file_name`], so only the path
+    /// separators in the template create directories.
+    async fn render_template(&self, template: &str, epub: &Epub) -> Result<String> {
+        let content_opf = epub.content_opf().await?;
+        let metadata = &content_opf.metadata;
+        let mut rendered = String::new();
+        let mut rest = template;
+
+        while let Some(start) = rest.find('{') {
+            rendered.push_str(&rest[..start]);
+
+            let Some(end) = rest[start..].find('}') else {
+                bail!("Unclosed placeholder in the rename template '{}'", template);
+            };
+            let value = match &rest[start + 1..start + end] {
+                "title" => metadata.title.clone(),
+                "author" => self.authors(metadata),
+                "author_sort" => metadata
+                    .creator()
+                    .map(Creator::sort_name)
+                    .unwrap_or_default(),
+                "year" => metadata
+                    .date
+                    .as_deref()
+                    .and_then(|date| date.get(..4))
+                    .filter(|year| year.chars().all(|c| c.is_ascii_digit()))
+                    .unwrap_or_default()
+                    .to_string(),
+                "isbn" => content_opf.isbn().unwrap_or_default(),
+                "language" => metadata.language.clone(),
+                placeholder => bail!(
+                    "Unknown placeholder '{{{}}}' in the rename template, expected one of \
+                     {{title}}, {{author}}, {{author_sort}}, {{year}}, {{isbn}} or {{language}}",
+                    placeholder
+                ),
+            };
+
+            rendered.push_str(&sanitize_file_name(&value));
+            rest = &rest[start + end + 1..];
+        }
+
+        rendered.push_str(rest);
+
+        if rendered
+            .split('/')
+            .any(|component| matches!(component.trim(), "" | "." | ".."))
+        {
+            bail!(
+                "The rename template '{}' renders to an invalid path '{}'",
+                template,
+                rendered
+            );
+        }
+
+        Ok(rendered)
+    }
+
+    async fn print_text(&self, epub: &Epub) -> Result<()> {
+        let content_opf = epub.content_opf().await?;
+
+        println!("Title: {}", content_opf.metadata.title);
+        println!(
+            "Author: {}",
+            content_opf
+                .metadata
+                .creator()
+                .map_or("", |creator| creator.name.as_str())
+        );
+        println!("Language: {}", content_opf.metadata.language);
+        println!("Identifier: {}", content_opf.primary_identifier());
+        println!("Version: {}", epub.version().await?);
+
+        let identifiers = epub.identifiers().await?;
+
+        if identifiers.len() > 1 {
+            println!("Identifiers:");
+
+            for identifier in identifiers {
+                println!(
+                    "  {}{}: {}",
+                    identifier.scheme.as_deref().unwrap_or("Unknown scheme"),
+                    if identifier.is_unique_id {
+                        " (unique)"
+                    } else {
+                        ""
+                    },
+                    identifier.value
+                );
+            }
+        }
+
+        let metadata = &content_opf.metadata;
+
+        if let Some(publisher) = &metadata.publisher {
+            println!("Publisher: {}", publisher);
+        }
+
+        if let Some(date) = &metadata.date {
+            println!("Date: {}", date);
+        }
+
+        if let Some(rights) = &metadata.rights {
+            println!("Rights: {}", rights);
+        }
+
+        if !metadata.subjects.is_empty() {
+            println!("Subjects: {}", metadata.subjects.join(", "));
+        }
+
+        if let Some(description) = &metadata.description {
+            println!("Description: {}", description);
+        }
+
+        println!(
+            "Kobo-enhanced: {}",
+            if epub.is_kepub().await? { "yes" } else { "no" }
+        );
+        println!(
+            "DRM: {}",
+            if epub.is_drm_protected().await? {
+                "yes"
+            } else {
+                "no"
+            }
+        );
+
+        if self.a11y {
+            if content_opf.metadata.accessibility.is_empty() {
+                println!("Accessibility: None declared");
+            } else {
+                println!("Accessibility:");
+
+                for (property, value) in &content_opf.metadata.accessibility {
+                    println!("  {}: {}", property, value);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Looks the book ISBN up in OpenLibrary, returning the `isbn` with the
+/// values of the fields missing in the book, or `null` for the ones it
+/// already has. Lookup failures are reported as warnings and never fail the
+/// command.
+#[cfg(feature = "online")]
+async fn enrichment(epub: &Epub) -> Result<Option<Value>> {
+    use bookworm::epub::{lookup_isbn, normalize_isbn};
+
+    let content_opf = epub.content_opf().await?;
+    let metadata = &content_opf.metadata;
+    let Some(isbn) = normalize_isbn(&epub.isbn().await?) else {
+        eprintln!("Warning: No ISBN found to enrich the metadata with");
+        return Ok(None);
+    };
+
+    // The lookup blocks for up to its timeout, so keep it off the runtime
+    let lookup = {
+        let isbn = isbn.clone();
+        tokio::task::spawn_blocking(move || lookup_isbn(&isbn)).await?
+    };
+    let record = match lookup {
+        Ok(Some(record)) => record,
+        Ok(None) => {
+            eprintln!("Warning: ISBN {} not found in OpenLibrary", isbn);
+            return Ok(None);
+        }
+        Err(err) => {
+            eprintln!("Warning: Failed to look up ISBN {}: {}", isbn, err);
+            return Ok(None);
+        }
+    };
+    let missing = |has_value: bool, value: Option<String>| value.filter(|_| !has_value);
+
+    Ok(Some(json!({
+        "source": "OpenLibrary",
+        "isbn": isbn,
+        "title": missing(!metadata.title.is_empty(), record.title),
+        "author": missing(
+            !metadata.creators.is_empty(),
+            Some(record.authors.join(", ")).filter(|authors| !authors.is_empty()),
+        ),
+        "publisher": missing(metadata.publisher.is_some(), record.publisher),
+        "cover": missing(content_opf.cover_item().is_some(), record.cover_url),
+    })))
+}
+
+/// Prints the fields found by [`enrichment`] below the book metadata
+fn print_enrichment(enrichment: &Value) {
+    println!(
+        "Enriched ({}, ISBN {}):",
+        enrichment["source"].as_str().unwrap_or_default(),
+        enrichment["isbn"].as_str().unwrap_or_default()
+    );
+
+    for (key, label) in [
+        ("title", "Title"),
+        ("author", "Author"),
+        ("publisher", "Publisher"),
+        ("cover", "Cover"),
+    ] {
+        if let Some(value) = enrichment[key].as_str() {
+            println!("  {}: {}", label, value);
+        }
+    }
+}
+
+/// Prints the book metadata as a single JSON object, with the OpenLibrary
+/// `enrichment` under `enriched` when looked up
+async fn print_json(epub: &Epub, enrichment: Option<Value>) -> Result<()> {
+    let content_opf = epub.content_opf().await?;
+    let metadata = &content_opf.metadata;
+    let non_empty = |value: &str| {
+        if value.is_empty() {
+            Value::Null
+        } else {
+            Value::from(value)
+        }
+    };
+    let accessibility = metadata
+        .accessibility
+        .iter()
+        .map(|(property, value)| json!({ "property": property, "value": value }))
+        .collect::<Vec<Value>>();
+    let identifiers = metadata
+        .identifiers
+        .iter()
+        .map(|identifier| {
+            json!({
+                "scheme": identifier.scheme,
+                "value": identifier.value,
+                "unique": identifier.is_unique_id,
+                "valid_isbn": is_valid_isbn(&identifier.value),
+            })
+        })
+        .collect::<Vec<Value>>();
+    let mut info = json!({
+        "title": non_empty(&metadata.title),
+        "author": metadata.creator().map(|creator| creator.name.as_str()),
+        "author_sort": metadata.creator().map(Creator::sort_name),
+        "language": non_empty(&metadata.language),
+        "identifier": non_empty(content_opf.primary_identifier()),
+        "publisher": metadata.publisher,
+        "description": metadata.description,
+        "date": metadata.date,
+        "rights": metadata.rights,
+        "subjects": metadata.subjects,
+        "isbn": non_empty(&epub.isbn().await?),
+        "identifiers": identifiers,
+        "spine_count": content_opf.spine.len(),
+        "epub_version": content_opf.version,
+        "kepub": epub.is_kepub().await?,
+        "drm": epub.is_drm_protected().await?,
+        "accessibility": accessibility,
+        "metadata": metadata,
+    });
+
+    if let Some(enrichment) = enrichment {
+        info["enriched"] = enrichment;
+    }
+
+    println!("{}", serde_json::to_string_pretty(&info)?);
+
+    Ok(())
 }
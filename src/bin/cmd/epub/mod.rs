@@ -1,10 +1,34 @@
+mod check;
+mod count;
+mod fix;
+mod fonts;
 mod info;
+mod package;
+mod put;
+mod rm;
+mod scan;
+mod split;
+mod stats;
+mod text;
+mod toc;
 mod unpackage;
 
 use anyhow::Result;
 use clap::Subcommand;
 
+use self::check::CheckOpt;
+use self::count::CountOpt;
+use self::fix::FixOpt;
+use self::fonts::FontsOpt;
 use self::info::InfoOpt;
+use self::package::PackageOpt;
+use self::put::PutOpt;
+use self::rm::RmOpt;
+use self::scan::ScanOpt;
+use self::split::SplitOpt;
+use self::stats::StatsOpt;
+use self::text::TextOpt;
+use self::toc::TocOpt;
 use self::unpackage::UnPackageOpt;
 
 #[derive(Clone, Debug, Subcommand)]
@@ -13,6 +37,30 @@ pub enum EpubCmd {
     Info(InfoOpt),
     /// Unpackage (K)Epub File
     Unpkg(UnPackageOpt),
+    /// Package a Directory into a (K)Epub File
+    Package(PackageOpt),
+    /// List (K)Epub Embedded Fonts
+    Fonts(FontsOpt),
+    /// Print the (K)Epub Table of Contents
+    Toc(TocOpt),
+    /// Split (K)Epub File into one (K)Epub per Chapter
+    Split(SplitOpt),
+    /// Extract the Plain Text of a (K)Epub File
+    Text(TextOpt),
+    /// Count the Words and Characters of a (K)Epub File
+    Count(CountOpt),
+    /// Check the Structural Integrity of a (K)Epub File
+    Check(CheckOpt),
+    /// Add or Replace a Single File inside a (K)Epub File
+    Put(PutOpt),
+    /// Remove a Single File from a (K)Epub File
+    Rm(RmOpt),
+    /// Print the Information of every (K)Epub File in a Directory
+    Scan(ScanOpt),
+    /// Fix the mimetype Entry of a (K)Epub File
+    Fix(FixOpt),
+    /// Print the Size of a (K)Epub File's Assets by Media Type
+    Stats(StatsOpt),
 }
 
 impl EpubCmd {
@@ -20,6 +68,18 @@ impl EpubCmd {
         match self {
             Self::Info(cmd) => cmd.exec().await,
             Self::Unpkg(cmd) => cmd.exec().await,
+            Self::Package(cmd) => cmd.exec().await,
+            Self::Fonts(cmd) => cmd.exec().await,
+            Self::Toc(cmd) => cmd.exec().await,
+            Self::Split(cmd) => cmd.exec().await,
+            Self::Text(cmd) => cmd.exec().await,
+            Self::Count(cmd) => cmd.exec().await,
+            Self::Check(cmd) => cmd.exec().await,
+            Self::Put(cmd) => cmd.exec().await,
+            Self::Rm(cmd) => cmd.exec().await,
+            Self::Scan(cmd) => cmd.exec().await,
+            Self::Fix(cmd) => cmd.exec().await,
+            Self::Stats(cmd) => cmd.exec().await,
         }
     }
 }
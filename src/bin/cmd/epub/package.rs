@@ -0,0 +1,34 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::Args;
+
+use bookworm::epub::{EpubWriter, inject_css};
+
+#[derive(Args, Clone, Debug)]
+pub struct PackageOpt {
+    /// Directory holding an unpackaged (K)Epub
+    source: PathBuf,
+    /// Path to write the (K)Epub file to
+    #[clap(long, short)]
+    output: PathBuf,
+    /// Copies back the stylesheets extracted with `epub unpkg --extract-css`
+    #[clap(long, value_name = "DIR")]
+    css: Option<PathBuf>,
+}
+
+impl PackageOpt {
+    pub async fn exec(&self) -> Result<()> {
+        if let Some(css_dir) = &self.css {
+            for path in inject_css(css_dir, &self.source)? {
+                println!("Injected stylesheet: {}", path);
+            }
+        }
+
+        EpubWriter::repack(&self.source, &self.output).await?;
+
+        println!("Packaged: {}", self.output.display());
+
+        Ok(())
+    }
+}
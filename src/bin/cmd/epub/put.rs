@@ -0,0 +1,39 @@
+use std::fs::read;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::Args;
+
+use bookworm::epub::Epub;
+
+#[derive(Args, Clone, Debug)]
+pub struct PutOpt {
+    /// Path to the (K)Epub file
+    file: PathBuf,
+    /// Path of the entry in the archive (e.g. `OEBPS/cover.jpg`)
+    #[clap(long)]
+    path: String,
+    /// File to read the new contents of the entry from
+    #[clap(long)]
+    from: PathBuf,
+    /// Adds the entry when the archive doesn't have it yet
+    #[clap(long)]
+    create: bool,
+}
+
+impl PutOpt {
+    pub async fn exec(&self) -> Result<()> {
+        let mut epub = Epub::open_async(&self.file).await?;
+        let bytes = read(&self.from)?;
+
+        if self.create {
+            epub.put_file(&self.path, &bytes)?;
+        } else {
+            epub.replace_file(&self.path, &bytes)?;
+        }
+
+        println!("Wrote: {}", self.path);
+
+        Ok(())
+    }
+}
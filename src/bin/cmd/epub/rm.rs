@@ -0,0 +1,27 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::Args;
+
+use bookworm::epub::Epub;
+
+#[derive(Args, Clone, Debug)]
+pub struct RmOpt {
+    /// Path to the (K)Epub file
+    file: PathBuf,
+    /// Path of the entry in the archive (e.g. `OEBPS/tracker.js`)
+    #[clap(long)]
+    path: String,
+}
+
+impl RmOpt {
+    pub async fn exec(&self) -> Result<()> {
+        let mut epub = Epub::open_async(&self.file).await?;
+
+        epub.remove_file(&self.path)?;
+
+        println!("Removed: {}", self.path);
+
+        Ok(())
+    }
+}
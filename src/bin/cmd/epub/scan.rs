@@ -0,0 +1,161 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use clap::{Args, ValueEnum};
+use glob::{MatchOptions, Pattern, glob_with};
+use serde_json::json;
+
+use bookworm::epub::Epub;
+
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+pub enum ScanFormat {
+    /// An aligned table with a row per book
+    #[default]
+    Text,
+    /// One JSON object per line (NDJSON), with an `error` key for the books
+    /// that failed to open
+    Json,
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct ScanOpt {
+    /// Directory to look for (K)Epub files in, recursively
+    dir: PathBuf,
+    /// Output format
+    #[clap(long, value_enum, default_value_t)]
+    format: ScanFormat,
+}
+
+/// The metadata printed for each book
+struct BookInfo {
+    title: String,
+    author: String,
+    language: String,
+    isbn: String,
+    version: String,
+}
+
+impl ScanOpt {
+    pub async fn exec(&self) -> Result<()> {
+        let pattern = format!("{}/**/*.epub", Pattern::escape(&self.dir.to_string_lossy()));
+        let options = MatchOptions {
+            case_sensitive: false,
+            ..MatchOptions::new()
+        };
+        let mut rows = Vec::new();
+        let mut failed = 0;
+
+        for path in glob_with(&pattern, options)? {
+            let path = match path {
+                Ok(path) => path,
+                Err(err) => {
+                    eprintln!("Error: {}", err);
+                    failed += 1;
+                    continue;
+                }
+            };
+            let info = book_info(&path).await;
+
+            if let Err(err) = &info {
+                failed += 1;
+
+                if let ScanFormat::Text = self.format {
+                    eprintln!("Error: {}: {}", path.display(), err);
+                }
+            }
+
+            match self.format {
+                ScanFormat::Json => print_json(&path, &info),
+                ScanFormat::Text => {
+                    if let Ok(info) = info {
+                        rows.push((path, info));
+                    }
+                }
+            }
+        }
+
+        if let ScanFormat::Text = self.format {
+            print_table(&rows);
+            println!("Scanned {} book(s), {} failed", rows.len() + failed, failed);
+        }
+
+        Ok(())
+    }
+}
+
+async fn book_info(path: &Path) -> Result<BookInfo> {
+    let epub = Epub::open_async(path).await?;
+    let content_opf = epub.content_opf().await?;
+    let metadata = &content_opf.metadata;
+
+    Ok(BookInfo {
+        title: metadata.title.clone(),
+        author: metadata
+            .creator()
+            .map(|creator| creator.name.clone())
+            .unwrap_or_default(),
+        language: metadata.language.clone(),
+        isbn: content_opf.isbn().unwrap_or_default(),
+        version: epub.version().await?.to_string(),
+    })
+}
+
+fn print_json(path: &Path, info: &Result<BookInfo>) {
+    let line = match info {
+        Ok(info) => json!({
+            "path": path,
+            "title": info.title,
+            "author": info.author,
+            "language": info.language,
+            "isbn": info.isbn,
+            "version": info.version,
+        }),
+        Err(err) => json!({
+            "path": path,
+            "error": err.to_string(),
+        }),
+    };
+
+    println!("{}", line);
+}
+
+fn print_table(rows: &[(PathBuf, BookInfo)]) {
+    const HEADER: [&str; 6] = ["Title", "Author", "Language", "ISBN", "Version", "Path"];
+
+    let cells = rows
+        .iter()
+        .map(|(path, info)| {
+            [
+                info.title.clone(),
+                info.author.clone(),
+                info.language.clone(),
+                info.isbn.clone(),
+                info.version.clone(),
+                path.display().to_string(),
+            ]
+        })
+        .collect::<Vec<[String; 6]>>();
+    let mut widths = HEADER.map(|header| header.chars().count());
+
+    for row in &cells {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let format_row = |row: &[&str]| {
+        row.iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect::<Vec<String>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+
+    println!("{}", format_row(&HEADER));
+
+    for row in &cells {
+        println!("{}", format_row(&row.each_ref().map(String::as_str)));
+    }
+}
@@ -0,0 +1,44 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::Args;
+
+use bookworm::epub::Epub;
+
+#[derive(Args, Clone, Debug)]
+pub struct SplitOpt {
+    /// Path to the (K)Epub file
+    path: PathBuf,
+    /// Directory to write the per-chapter (K)Epub files into
+    #[clap(long, short)]
+    output: Option<PathBuf>,
+}
+
+impl SplitOpt {
+    pub async fn exec(&self) -> Result<()> {
+        let outdir = match &self.output {
+            Some(dir) => dir.clone(),
+            None => {
+                let parent = self
+                    .path
+                    .parent()
+                    .ok_or_else(|| anyhow::anyhow!("Failed to get parent directory"))?;
+                let file_stem = self
+                    .path
+                    .file_stem()
+                    .and_then(|stem| stem.to_str())
+                    .ok_or_else(|| anyhow::anyhow!("Failed to get file stem"))?;
+
+                parent.join(file_stem)
+            }
+        };
+
+        let epub = Epub::open_async(&self.path).await?;
+
+        for path in epub.split(&outdir).await? {
+            println!("Created: {}", path.display());
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,43 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::Args;
+
+use bookworm::epub::Epub;
+
+#[derive(Args, Clone, Debug)]
+pub struct StatsOpt {
+    /// Path to the (K)Epub file
+    path: PathBuf,
+}
+
+impl StatsOpt {
+    pub async fn exec(&self) -> Result<()> {
+        let epub = Epub::open_async(&self.path).await?;
+        let stats = epub.asset_summary().await?;
+        let total_count = stats.iter().map(|stat| stat.count).sum::<usize>();
+        let total_bytes = stats.iter().map(|stat| stat.total_bytes).sum::<u64>();
+        let width = stats
+            .iter()
+            .map(|stat| stat.media_type.chars().count())
+            .chain(["Media Type".len()])
+            .max()
+            .unwrap_or_default();
+
+        println!("{:<width$}  {:>5}  {:>12}", "Media Type", "Files", "Bytes");
+
+        for stat in &stats {
+            println!(
+                "{:<width$}  {:>5}  {:>12}",
+                stat.media_type, stat.count, stat.total_bytes
+            );
+        }
+
+        println!(
+            "{:<width$}  {:>5}  {:>12}",
+            "Total", total_count, total_bytes
+        );
+
+        Ok(())
+    }
+}
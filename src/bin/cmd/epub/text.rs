@@ -0,0 +1,33 @@
+use std::fs::write;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::Args;
+
+use bookworm::epub::Epub;
+
+#[derive(Args, Clone, Debug)]
+pub struct TextOpt {
+    /// Path to the (K)Epub file
+    path: PathBuf,
+    /// File to write the text into, instead of stdout
+    #[clap(long, short)]
+    output: Option<PathBuf>,
+}
+
+impl TextOpt {
+    pub async fn exec(&self) -> Result<()> {
+        let epub = Epub::open_async(&self.path).await?;
+        let text = epub.text().await?;
+
+        match &self.output {
+            Some(output) => {
+                write(output, format!("{}\n", text))?;
+                println!("Saved text to: {}", output.display());
+            }
+            None => println!("{}", text),
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,73 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::{Args, ValueEnum};
+use serde_json::{Value, json};
+
+use bookworm::epub::{Epub, NavPoint};
+
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+pub enum TocFormat {
+    /// Indented outline
+    #[default]
+    Text,
+    /// Nested JSON array of `{label, href, children}` objects
+    Json,
+    /// Nested Markdown list of links
+    Md,
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct TocOpt {
+    /// Path to the (K)Epub file
+    path: PathBuf,
+    /// Output format
+    #[clap(long, value_enum, default_value_t)]
+    format: TocFormat,
+}
+
+impl TocOpt {
+    pub async fn exec(&self) -> Result<()> {
+        let epub = Epub::open_async(&self.path).await?;
+        let nav_points = epub.toc().await?.nav_points();
+
+        match self.format {
+            TocFormat::Text => print_outline(nav_points, 0, false),
+            TocFormat::Md => print_outline(nav_points, 0, true),
+            TocFormat::Json => println!("{}", serde_json::to_string_pretty(&to_json(nav_points))?),
+        }
+
+        Ok(())
+    }
+}
+
+fn print_outline(nav_points: &[NavPoint], depth: usize, markdown: bool) {
+    let indent = "  ".repeat(depth);
+
+    for nav_point in nav_points {
+        if markdown {
+            println!(
+                "{}- [{}]({})",
+                indent, nav_point.label, nav_point.content_src
+            );
+        } else {
+            println!("{}{} ({})", indent, nav_point.label, nav_point.content_src);
+        }
+
+        print_outline(&nav_point.children, depth + 1, markdown);
+    }
+}
+
+fn to_json(nav_points: &[NavPoint]) -> Value {
+    nav_points
+        .iter()
+        .map(|nav_point| {
+            json!({
+                "label": nav_point.label,
+                "href": nav_point.content_src,
+                "play_order": nav_point.play_order,
+                "children": to_json(&nav_point.children),
+            })
+        })
+        .collect()
+}
@@ -2,6 +2,7 @@ use std::path::PathBuf;
 
 use anyhow::Result;
 use clap::Args;
+use glob::Pattern;
 
 use bookworm::epub::Epub;
 
@@ -12,6 +13,15 @@ pub struct UnPackageOpt {
     /// Directory to unpackage the (K)Epub file into
     #[clap(long, short)]
     output: Option<PathBuf>,
+    /// Reverts the IDPF/Adobe obfuscation of embedded fonts
+    #[clap(long)]
+    deobfuscate: bool,
+    /// Only extracts the entries matching the glob pattern (e.g. `*.opf`)
+    #[clap(long, value_name = "GLOB", conflicts_with = "deobfuscate")]
+    only: Option<Pattern>,
+    /// Also copies every stylesheet into this flat directory for editing
+    #[clap(long, value_name = "DIR", conflicts_with = "only")]
+    extract_css: Option<PathBuf>,
 }
 
 impl UnPackageOpt {
@@ -33,8 +43,43 @@ impl UnPackageOpt {
             }
         };
 
+        if let Some(pattern) = &self.only {
+            let epub = Epub::open_async(&self.path).await?;
+            let mut extracted = 0;
+
+            for name in epub.list_entries().await? {
+                if pattern.matches(&name) {
+                    epub.extract_entry(&name, &outdir).await?;
+                    println!("Extracted: {}", name);
+                    extracted += 1;
+                }
+            }
+
+            if extracted == 0 {
+                anyhow::bail!("No entries match '{}'", pattern);
+            }
+
+            return Ok(());
+        }
+
         Epub::unpackage(&self.path, &outdir)?;
 
+        if self.deobfuscate {
+            let epub = Epub::open_async(&self.path).await?;
+
+            for font in epub.deobfuscate_fonts(&outdir).await? {
+                println!("De-obfuscated font: {}", font);
+            }
+        }
+
+        if let Some(css_dir) = &self.extract_css {
+            let epub = Epub::open_async(&self.path).await?;
+
+            for (file_name, path) in epub.extract_css(css_dir).await? {
+                println!("Extracted stylesheet: {} -> {}", path, file_name);
+            }
+        }
+
         Ok(())
     }
 }
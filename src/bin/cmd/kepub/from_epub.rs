@@ -0,0 +1,37 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::Args;
+
+use bookworm::epub::Epub;
+
+#[derive(Args, Clone, Debug)]
+pub struct FromEpubOpt {
+    /// Path to the Epub file
+    path: PathBuf,
+    /// Path to write the KEpub file to, defaults to the input path with the
+    /// `.kepub.epub` extension
+    #[clap(long, short)]
+    output: Option<PathBuf>,
+}
+
+impl FromEpubOpt {
+    pub async fn exec(&self) -> Result<()> {
+        let epub = Epub::open_async(&self.path).await?;
+
+        if epub.is_kepub().await? {
+            eprintln!("Warning: The book is already Kobo-enhanced, its spans are kept as is");
+        }
+
+        let output = match &self.output {
+            Some(output) => output.clone(),
+            None => self.path.with_extension("kepub.epub"),
+        };
+
+        epub.to_kepub(&output).await?;
+
+        println!("Converted: {}", output.display());
+
+        Ok(())
+    }
+}
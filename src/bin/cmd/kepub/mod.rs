@@ -0,0 +1,20 @@
+mod from_epub;
+
+use anyhow::Result;
+use clap::Subcommand;
+
+use self::from_epub::FromEpubOpt;
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum KepubCmd {
+    /// Convert an Epub File into a Kobo-enhanced KEpub File
+    FromEpub(FromEpubOpt),
+}
+
+impl KepubCmd {
+    pub async fn exec(&self) -> Result<()> {
+        match self {
+            Self::FromEpub(cmd) => cmd.exec().await,
+        }
+    }
+}
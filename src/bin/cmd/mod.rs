@@ -1,2 +1,4 @@
+pub mod convert;
 pub mod epub;
+pub mod kepub;
 pub mod pdf;
@@ -0,0 +1,44 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use bookworm::pdf::{MetadataChange, Pdf};
+use clap::Args;
+
+#[derive(Args, Clone, Debug)]
+pub struct CompareOpt {
+    /// Path to the original PDF file
+    before: PathBuf,
+    /// Path to the PDF file to compare against
+    after: PathBuf,
+}
+
+impl CompareOpt {
+    pub async fn exec(&self) -> Result<()> {
+        let before = Pdf::open(&self.before)?;
+        let after = Pdf::open(&self.after)?;
+        let comparison = before.compare(&after);
+
+        if comparison.is_unchanged() {
+            println!("No metadata differences found");
+            return Ok(());
+        }
+
+        for change in &comparison.changes {
+            match change {
+                MetadataChange::Added { key, value } => println!("+ {}: {}", key, value),
+                MetadataChange::Removed { key, value } => println!("- {}: {}", key, value),
+                MetadataChange::Changed { key, from, to } => {
+                    println!("~ {}: {} -> {}", key, from, to)
+                }
+            }
+        }
+
+        let (before_pages, after_pages) = comparison.page_counts;
+
+        if before_pages != after_pages {
+            println!("~ Pages: {} -> {}", before_pages, after_pages);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,73 @@
+use std::path::PathBuf;
+
+use anyhow::{Result, bail};
+use bookworm::pdf::{Pdf, PdfMetaField};
+use clap::Args;
+
+#[derive(Args, Clone, Debug)]
+pub struct ClearOpt {
+    /// Path to the PDF file
+    path: PathBuf,
+    /// Removes the document title
+    #[clap(long)]
+    title: bool,
+    /// Removes the document author
+    #[clap(long)]
+    author: bool,
+    /// Removes the document subject
+    #[clap(long)]
+    subject: bool,
+    /// Removes the document keywords
+    #[clap(long)]
+    keywords: bool,
+    /// Removes the application that created the original document
+    #[clap(long)]
+    creator: bool,
+    /// Removes the application that produced the PDF
+    #[clap(long)]
+    producer: bool,
+    /// Path to write the updated PDF file into, defaults to `clear_info.pdf`
+    /// next to the source file
+    #[clap(long, short)]
+    output: Option<PathBuf>,
+}
+
+impl ClearOpt {
+    pub async fn exec(&self) -> Result<()> {
+        let fields = [
+            (PdfMetaField::Title, self.title),
+            (PdfMetaField::Author, self.author),
+            (PdfMetaField::Subject, self.subject),
+            (PdfMetaField::Keywords, self.keywords),
+            (PdfMetaField::Creator, self.creator),
+            (PdfMetaField::Producer, self.producer),
+        ];
+
+        if fields.iter().all(|(_, clear)| !clear) {
+            bail!("No fields to clear, pass at least one field flag (e.g. --author)");
+        }
+
+        let mut pdf = Pdf::open(&self.path)?;
+
+        for (field, clear) in fields {
+            if clear {
+                pdf = pdf.clear_metadata(&field)?;
+            }
+        }
+
+        let output = match &self.output {
+            Some(output) => output.clone(),
+            None => self
+                .path
+                .parent()
+                .ok_or_else(|| anyhow::anyhow!("Failed to get parent directory"))?
+                .join("clear_info.pdf"),
+        };
+
+        pdf.save(&output)?;
+
+        println!("Saved updated PDF to: {}", output.display());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,156 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use bookworm::pdf::{Pdf, PdfMetaField, PdfMetadata};
+use clap::{Args, ValueEnum};
+use serde::Serialize;
+
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+pub enum InfoFormat {
+    /// Human readable `Field: value` lines
+    #[default]
+    Text,
+    /// A single JSON object, with `null` for missing fields
+    Json,
+}
+
+#[derive(Serialize)]
+struct PdfInfo {
+    #[serde(flatten)]
+    metadata: PdfMetadata,
+    language: Option<String>,
+    pages: usize,
+    encrypted: bool,
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct GetOpt {
+    /// Path to the PDF file
+    path: PathBuf,
+    /// Password to decrypt an encrypted PDF with
+    #[clap(long)]
+    password: Option<String>,
+    /// Output format, the extra sections below are only printed as text
+    #[clap(long, value_enum, default_value_t)]
+    format: InfoFormat,
+    /// Also prints every Info dictionary entry, including custom keys
+    #[clap(long)]
+    all: bool,
+    /// Also prints the undecoded bytes of each field as hex
+    #[clap(long)]
+    raw_bytes: bool,
+    /// Also prints the XMP metadata packet, if any
+    #[clap(long)]
+    xmp: bool,
+}
+
+impl GetOpt {
+    pub async fn exec(&self) -> Result<()> {
+        let pdf = match &self.password {
+            Some(password) => Pdf::open_with_password(&self.path, password)?,
+            None => Pdf::open_metadata_only(&self.path)?,
+        };
+        let info = pdf.metadata()?;
+
+        if pdf.is_locked() {
+            eprintln!(
+                "Warning: The PDF is encrypted and requires a password, fields may be unreadable"
+            );
+        }
+
+        if let InfoFormat::Json = self.format {
+            let info = PdfInfo {
+                metadata: info,
+                language: pdf.language(),
+                pages: pdf.page_count(),
+                encrypted: pdf.is_encrypted(),
+            };
+
+            println!("{}", serde_json::to_string_pretty(&info)?);
+
+            return Ok(());
+        }
+
+        println!(
+            "Title: {}",
+            info.title.unwrap_or_else(|| "Unknown".to_string())
+        );
+        println!(
+            "Author: {}",
+            info.author.unwrap_or_else(|| "Unknown".to_string())
+        );
+        println!(
+            "Subject: {}",
+            info.subject.unwrap_or_else(|| "Unknown".to_string())
+        );
+        println!(
+            "Keywords: {}",
+            info.keywords.unwrap_or_else(|| "Unknown".to_string())
+        );
+        println!(
+            "Creator: {}",
+            info.creator.unwrap_or_else(|| "Unknown".to_string())
+        );
+        println!(
+            "Producer: {}",
+            info.producer.unwrap_or_else(|| "Unknown".to_string())
+        );
+        println!(
+            "Creation Date: {}",
+            info.creation_date.unwrap_or_else(|| "Unknown".to_string())
+        );
+        println!(
+            "Modification Date: {}",
+            info.modification_date
+                .unwrap_or_else(|| "Unknown".to_string())
+        );
+        println!(
+            "Language: {}",
+            pdf.language().unwrap_or_else(|| "Unknown".to_string())
+        );
+        println!("Pages: {}", pdf.page_count());
+        println!(
+            "Encrypted: {}",
+            if pdf.is_encrypted() { "yes" } else { "no" }
+        );
+
+        if self.all {
+            println!("All:");
+
+            for (key, value) in pdf.all_metadata() {
+                println!("  {}: {}", key, value);
+            }
+        }
+
+        if self.raw_bytes {
+            println!("Raw bytes:");
+
+            for field in PdfMetaField::ALL {
+                let hex = pdf.get_metadata_field_raw(&field).map(|bytes| {
+                    bytes
+                        .iter()
+                        .map(|byte| format!("{:02x}", byte))
+                        .collect::<Vec<String>>()
+                        .join(" ")
+                });
+
+                println!("  {}: {}", field, hex.unwrap_or_else(|| "None".to_string()));
+            }
+        }
+
+        if self.xmp {
+            match pdf.xmp_metadata()? {
+                Some(xmp) => {
+                    println!(
+                        "XMP Title: {}",
+                        pdf.xmp_title()?.unwrap_or_else(|| "Unknown".to_string())
+                    );
+                    println!("XMP:\n{}", xmp);
+                }
+                None => println!("XMP: None"),
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,30 @@
+mod clear;
+mod get;
+mod set;
+
+use anyhow::Result;
+use clap::Subcommand;
+
+use self::clear::ClearOpt;
+use self::get::GetOpt;
+use self::set::SetOpt;
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum InfoCmd {
+    /// Retrieve PDF File Information
+    Get(GetOpt),
+    /// Update PDF File Information
+    Set(SetOpt),
+    /// Remove PDF File Information Fields
+    Clear(ClearOpt),
+}
+
+impl InfoCmd {
+    pub async fn exec(&self) -> Result<()> {
+        match self {
+            Self::Get(cmd) => cmd.exec().await,
+            Self::Set(cmd) => cmd.exec().await,
+            Self::Clear(cmd) => cmd.exec().await,
+        }
+    }
+}
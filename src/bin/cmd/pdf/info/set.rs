@@ -0,0 +1,89 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use bookworm::pdf::{Pdf, PdfMetaField, SaveOptions};
+use clap::Args;
+
+#[derive(Args, Clone, Debug)]
+pub struct SetOpt {
+    /// Path to the PDF file
+    path: PathBuf,
+    /// Password to decrypt an encrypted PDF with
+    #[clap(long)]
+    password: Option<String>,
+    /// Document title
+    #[clap(long)]
+    title: Option<String>,
+    /// Document author
+    #[clap(long)]
+    author: Option<String>,
+    /// Application that created the original document
+    #[clap(long)]
+    creator: Option<String>,
+    /// Application that produced the PDF
+    #[clap(long)]
+    producer: Option<String>,
+    /// Document language as a BCP 47 tag (e.g. `en-US`)
+    #[clap(long)]
+    lang: Option<String>,
+    /// Stamps the modification date with the current time
+    #[clap(long)]
+    update_mod_date: bool,
+    /// Path to write the updated PDF file into, defaults to `set_info.pdf`
+    /// next to the source file
+    #[clap(long, short)]
+    output: Option<PathBuf>,
+    /// Overwrites the source file instead of writing a new one
+    #[clap(long, conflicts_with = "output")]
+    in_place: bool,
+}
+
+impl SetOpt {
+    pub async fn exec(&self) -> Result<()> {
+        let mut pdf = match &self.password {
+            Some(password) => Pdf::open_with_password(&self.path, password)?,
+            None => Pdf::open(&self.path)?,
+        };
+        let fields = [
+            (PdfMetaField::Title, &self.title),
+            (PdfMetaField::Author, &self.author),
+            (PdfMetaField::Creator, &self.creator),
+            (PdfMetaField::Producer, &self.producer),
+        ];
+
+        for (field, value) in fields {
+            if let Some(value) = value {
+                pdf = pdf.set_metadata(&field, value)?;
+            }
+        }
+
+        if let Some(lang) = &self.lang {
+            pdf = pdf.set_language(lang)?;
+        }
+
+        let options = SaveOptions {
+            update_mod_date: self.update_mod_date,
+        };
+
+        if self.in_place {
+            pdf.save_in_place_with(options)?;
+            println!("Updated PDF in place: {}", self.path.display());
+            return Ok(());
+        }
+
+        let output = match &self.output {
+            Some(output) => output.clone(),
+            None => self
+                .path
+                .parent()
+                .ok_or_else(|| anyhow::anyhow!("Failed to get parent directory"))?
+                .join("set_info.pdf"),
+        };
+
+        pdf.save_with(&output, options)?;
+
+        println!("Saved updated PDF to: {}", output.display());
+
+        Ok(())
+    }
+}
@@ -1,20 +1,36 @@
+mod compare;
 mod info;
+mod strip;
+mod text;
 
 use anyhow::Result;
 use clap::Subcommand;
 
-use self::info::InfoOpt;
+use self::compare::CompareOpt;
+use self::info::InfoCmd;
+use self::strip::StripOpt;
+use self::text::TextOpt;
 
 #[derive(Clone, Debug, Subcommand)]
 pub enum PdfCmd {
-    /// Retrieve PDF File Information
-    Info(InfoOpt),
+    /// Manage PDF File Information
+    #[clap(subcommand)]
+    Info(InfoCmd),
+    /// Compare the metadata of two PDF Files
+    Compare(CompareOpt),
+    /// Extract the text of a PDF File page by page
+    Text(TextOpt),
+    /// Remove every metadata entry from a PDF File
+    Strip(StripOpt),
 }
 
 impl PdfCmd {
     pub async fn exec(&self) -> Result<()> {
         match self {
             Self::Info(cmd) => cmd.exec().await,
+            Self::Compare(cmd) => cmd.exec().await,
+            Self::Text(cmd) => cmd.exec().await,
+            Self::Strip(cmd) => cmd.exec().await,
         }
     }
 }
@@ -0,0 +1,37 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use bookworm::pdf::{MetadataChange, Pdf};
+use clap::Args;
+
+#[derive(Args, Clone, Debug)]
+pub struct StripOpt {
+    /// Path to the PDF file
+    path: PathBuf,
+    /// Path to write the PDF file without metadata into
+    #[clap(long, short)]
+    output: PathBuf,
+}
+
+impl StripOpt {
+    pub async fn exec(&self) -> Result<()> {
+        let pdf = Pdf::open(&self.path)?;
+        let mut stripped = pdf.strip_all_metadata()?;
+
+        for change in pdf.compare(&stripped).changes {
+            if let MetadataChange::Removed { key, value } = change {
+                println!("Removed {}: {}", key, value);
+            }
+        }
+
+        if pdf.has_xmp_metadata() {
+            println!("Removed XMP metadata stream");
+        }
+
+        stripped.save(&self.output)?;
+
+        println!("Saved PDF without metadata to: {}", self.output.display());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,45 @@
+use std::io::{Write, stdout};
+use std::path::PathBuf;
+
+use anyhow::Result;
+use bookworm::pdf::Pdf;
+use clap::{Args, ValueEnum};
+use serde_json::json;
+
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+pub enum TextFormat {
+    /// Page texts concatenated as plain text
+    #[default]
+    Text,
+    /// One `{"page": N, "text": "..."}` JSON object per line
+    Jsonl,
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct TextOpt {
+    /// Path to the PDF file
+    path: PathBuf,
+    /// Output format, pages are written as soon as they are extracted
+    #[clap(long, value_enum, default_value_t)]
+    format: TextFormat,
+}
+
+impl TextOpt {
+    pub async fn exec(&self) -> Result<()> {
+        let pdf = Pdf::open(&self.path)?;
+        let mut stdout = stdout().lock();
+
+        for (page, text) in pdf.pages_text() {
+            let text = text?;
+
+            match self.format {
+                TextFormat::Text => writeln!(stdout, "{}", text)?,
+                TextFormat::Jsonl => writeln!(stdout, "{}", json!({ "page": page, "text": text }))?,
+            }
+
+            stdout.flush()?;
+        }
+
+        Ok(())
+    }
+}
@@ -4,7 +4,7 @@ use anyhow::Result;
 
 use clap::Parser;
 
-use crate::cmd::{epub::EpubCmd, pdf::PdfCmd};
+use crate::cmd::{convert::ConvertOpt, epub::EpubCmd, kepub::KepubCmd, pdf::PdfCmd};
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -31,16 +31,23 @@ pub enum Command {
     /// Manage EPUB Files
     #[clap(subcommand)]
     Epub(EpubCmd),
+    /// Manage Kobo-enhanced KEPUB Files
+    #[clap(subcommand)]
+    Kepub(KepubCmd),
     /// Manage PDF Files
     #[clap(subcommand)]
     Pdf(PdfCmd),
+    /// Convert between Ebook Formats
+    Convert(ConvertOpt),
 }
 
 impl Command {
     pub async fn exec(self) -> Result<()> {
         match self {
             Self::Epub(cmd) => cmd.exec().await,
+            Self::Kepub(cmd) => cmd.exec().await,
             Self::Pdf(cmd) => cmd.exec().await,
+            Self::Convert(cmd) => cmd.exec().await,
         }
     }
 }
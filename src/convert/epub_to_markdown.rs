@@ -0,0 +1,263 @@
+use std::collections::HashMap;
+use std::fs::write;
+use std::path::Path;
+
+use anyhow::Result;
+
+use crate::epub::Epub;
+use crate::epub::text::html_to_blocks;
+
+/// Converts an EPUB into a Markdown document with a `#` title, a `*by*`
+/// author line and one `##` section per linear spine document.
+///
+/// Each section is titled after the first heading of its document, or
+/// `Chapter N` when it has none. Other headings become `###` lines and
+/// paragraphs are separated by a blank line. The book text is escaped, so
+/// it never turns into Markdown markup.
+pub struct EpubToMarkdown<'a> {
+    epub: &'a Epub,
+    generate_toc: bool,
+}
+
+impl<'a> EpubToMarkdown<'a> {
+    pub fn new(epub: &'a Epub) -> Self {
+        EpubToMarkdown {
+            epub,
+            generate_toc: false,
+        }
+    }
+
+    /// Emits a table of contents after the title, linking each section with
+    /// GitHub-style heading anchors.
+    pub fn generate_toc(mut self, generate_toc: bool) -> Self {
+        self.generate_toc = generate_toc;
+        self
+    }
+
+    /// Renders the book into Markdown
+    pub async fn convert(&self) -> Result<String> {
+        self.epub.ensure_not_drm_protected().await?;
+
+        let content_opf = self.epub.content_opf().await?;
+        let metadata = &content_opf.metadata;
+        let mut chapters = Vec::new();
+
+        for (index, spine_item) in content_opf.spine.iter().enumerate() {
+            if !spine_item.linear {
+                continue;
+            }
+
+            let bytes = self.epub.spine_document(index).await?;
+            let mut blocks = html_to_blocks(&String::from_utf8_lossy(&bytes));
+
+            if blocks.is_empty() {
+                continue;
+            }
+
+            let title = match blocks.iter().position(|block| block.heading.is_some()) {
+                Some(position) => blocks.remove(position).text.replace('\n', " "),
+                None => format!("Chapter {}", chapters.len() + 1),
+            };
+            let body = blocks
+                .into_iter()
+                .map(|block| match block.heading {
+                    Some(_) => format!("### {}", escape(&block.text.replace('\n', " "))),
+                    None => escape(&block.text).replace('\n', "  \n"),
+                })
+                .collect::<Vec<String>>();
+
+            chapters.push((title, body));
+        }
+
+        let mut markdown = Vec::new();
+
+        if !metadata.title.is_empty() {
+            markdown.push(format!("# {}", escape(&metadata.title)));
+        }
+
+        if let Some(creator) = metadata.creator() {
+            markdown.push(format!("*by {}*", escape(&creator.name)));
+        }
+
+        if self.generate_toc && !chapters.is_empty() {
+            let mut anchors = HashMap::new();
+            let entries = chapters
+                .iter()
+                .map(|(title, _)| {
+                    format!("- [{}](#{})", escape(title), anchor(title, &mut anchors))
+                })
+                .collect::<Vec<String>>();
+
+            markdown.push("## Table of Contents".to_string());
+            markdown.push(entries.join("\n"));
+        }
+
+        for (title, body) in chapters {
+            markdown.push(format!("## {}", escape(&title)));
+            markdown.extend(body);
+        }
+
+        Ok(markdown.join("\n\n") + "\n")
+    }
+
+    /// Renders the book and writes the Markdown to `path`
+    pub async fn write<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        write(path, self.convert().await?)?;
+        Ok(())
+    }
+}
+
+/// Escapes the Markdown metacharacters in `text`: emphasis, code and link
+/// brackets anywhere, and at the start of a line whatever would turn it into
+/// a heading, a list item, a quote or a rule.
+fn escape(text: &str) -> String {
+    text.split('\n')
+        .map(|line| {
+            let mut escaped = String::with_capacity(line.len());
+
+            for c in line.chars() {
+                if matches!(c, '\\' | '*' | '_' | '`' | '[' | ']') {
+                    escaped.push('\\');
+                }
+
+                escaped.push(c);
+            }
+
+            let content = escaped.trim_start();
+            let indent = escaped.len() - content.len();
+            let digits = content.len()
+                - content
+                    .trim_start_matches(|c: char| c.is_ascii_digit())
+                    .len();
+
+            if content.starts_with(['#', '-', '+', '>', '=']) {
+                escaped.insert(indent, '\\');
+            } else if digits > 0 && content[digits..].starts_with(['.', ')']) {
+                escaped.insert(indent + digits, '\\');
+            }
+
+            escaped
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Returns the anchor GitHub generates for a heading: lowercased, without
+/// punctuation and with spaces turned into dashes. Repeated headings get a
+/// `-N` suffix, tracked in `seen`. The table of contents heading itself
+/// takes the first `table-of-contents` anchor.
+fn anchor(heading: &str, seen: &mut HashMap<String, usize>) -> String {
+    if seen.is_empty() {
+        seen.insert("table-of-contents".to_string(), 1);
+    }
+
+    let anchor = heading
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            '-' | '_' => Some(c),
+            c if c.is_alphanumeric() => Some(c),
+            _ => None,
+        })
+        .collect::<String>();
+    let count = seen.entry(anchor.clone()).or_default();
+    *count += 1;
+
+    match *count {
+        1 => anchor,
+        count => format!("{}-{}", anchor, count - 1),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use anyhow::Result;
+
+    use super::{EpubToMarkdown, anchor, escape};
+    use crate::epub::test_util::{EpubBuilder, content_opf};
+
+    #[tokio::test]
+    async fn converts_chapters_into_sections() -> Result<()> {
+        let epub = EpubBuilder::new(&content_opf(
+            "<dc:title>Markdown Book</dc:title>
+        <dc:creator>Jane Doe</dc:creator>
+        <dc:identifier id=\"BookId\">urn:uuid:1234</dc:identifier>",
+            &[
+                ("ch1", "ch1.xhtml", "application/xhtml+xml"),
+                ("ch2", "ch2.xhtml", "application/xhtml+xml"),
+            ],
+        ))
+        .entry(
+            "ch1.xhtml",
+            "<html><body><h1>The Start</h1><p>It was a dark night.</p>\
+             <h2>Later</h2><p>The sun rose.</p></body></html>",
+        )
+        .entry(
+            "ch2.xhtml",
+            "<html><body><p># 1 was *not* a heading</p><p>1. Nor a list</p></body></html>",
+        )
+        .open()?;
+        let markdown = EpubToMarkdown::new(&epub)
+            .generate_toc(true)
+            .convert()
+            .await?;
+
+        assert_eq!(
+            markdown,
+            "# Markdown Book
+
+*by Jane Doe*
+
+## Table of Contents
+
+- [The Start](#the-start)
+- [Chapter 2](#chapter-2)
+
+## The Start
+
+It was a dark night.
+
+### Later
+
+The sun rose.
+
+## Chapter 2
+
+\\# 1 was \\*not\\* a heading
+
+1\\. Nor a list
+"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn escapes_markdown_metacharacters() {
+        assert_eq!(escape("- a [link](url)"), "\\- a \\[link\\](url)");
+        assert_eq!(escape("snake_case\n  > quote"), "snake\\_case\n  \\> quote");
+        assert_eq!(escape("2024) was a year"), "2024\\) was a year");
+        assert_eq!(escape("Chapter 1. Plain"), "Chapter 1. Plain");
+    }
+
+    #[test]
+    fn builds_github_heading_anchors() {
+        let mut seen = HashMap::new();
+
+        assert_eq!(
+            anchor("Chapter 1: The Start!", &mut seen),
+            "chapter-1-the-start"
+        );
+        assert_eq!(
+            anchor("Chapter 1: The Start", &mut seen),
+            "chapter-1-the-start-1"
+        );
+        assert_eq!(
+            anchor("Table of Contents", &mut seen),
+            "table-of-contents-1"
+        );
+    }
+}
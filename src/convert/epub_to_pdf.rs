@@ -0,0 +1,305 @@
+use std::path::Path;
+
+use anyhow::Result;
+use lopdf::content::{Content, Operation};
+use lopdf::{Dictionary, Document, Object, Stream, dictionary};
+
+use crate::epub::Epub;
+use crate::epub::text::{TextBlock, html_to_blocks};
+use crate::pdf::encode_text_string;
+
+/// A4 page size, in points
+const PAGE_WIDTH: f32 = 595.0;
+const PAGE_HEIGHT: f32 = 842.0;
+const MARGIN: f32 = 72.0;
+const BODY_FONT_SIZE: f32 = 11.0;
+/// Line height as a multiple of the font size
+const LINE_SPACING: f32 = 1.4;
+/// Average glyph width of Helvetica as a fraction of the font size, used to
+/// wrap lines without measuring each glyph
+const AVERAGE_GLYPH_WIDTH: f32 = 0.5;
+const BODY_FONT: &str = "F1";
+const HEADING_FONT: &str = "F2";
+
+/// Converts an EPUB into a PDF with the plain text of its spine documents,
+/// in reading order, each one starting on a new page.
+///
+/// This is a first cut: paragraphs are flowed in Helvetica with headings in
+/// bold, while images, styles and links are dropped. Characters outside of
+/// the Windows-1252 charset are replaced by `?`.
+pub struct EpubToPdf<'a> {
+    epub: &'a Epub,
+}
+
+impl<'a> EpubToPdf<'a> {
+    pub fn new(epub: &'a Epub) -> Self {
+        EpubToPdf { epub }
+    }
+
+    /// Renders the book into a PDF document, carrying over the title, the
+    /// author and the language.
+    pub async fn convert(&self) -> Result<Document> {
+        self.epub.ensure_not_drm_protected().await?;
+
+        let content_opf = self.epub.content_opf().await?;
+        let mut layout = Layout::default();
+
+        for (index, spine_item) in content_opf.spine.iter().enumerate() {
+            if !spine_item.linear {
+                continue;
+            }
+
+            let bytes = self.epub.spine_document(index).await?;
+
+            layout.new_page();
+
+            for block in html_to_blocks(&String::from_utf8_lossy(&bytes)) {
+                layout.push_block(&block);
+            }
+        }
+
+        let metadata = &content_opf.metadata;
+        let mut doc = layout.into_document()?;
+        let mut info = Dictionary::new();
+
+        if !metadata.title.is_empty() {
+            info.set("Title", encode_text_string(&metadata.title));
+        }
+
+        if let Some(creator) = metadata.creator() {
+            info.set("Author", encode_text_string(&creator.name));
+        }
+
+        info.set("Creator", Object::string_literal("bookworm"));
+
+        let info_id = doc.add_object(info);
+        doc.trailer.set("Info", info_id);
+
+        if !metadata.language.is_empty() {
+            doc.catalog_mut()?
+                .set("Lang", encode_text_string(&metadata.language));
+        }
+
+        Ok(doc)
+    }
+
+    /// Renders the book and writes the PDF to `path`
+    pub async fn write<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        self.convert().await?.save(path)?;
+        Ok(())
+    }
+}
+
+/// Flows text blocks into pages, top to bottom
+#[derive(Default)]
+struct Layout {
+    pages: Vec<Vec<Operation>>,
+    /// Baseline of the next line on the current page
+    y: f32,
+}
+
+impl Layout {
+    fn new_page(&mut self) {
+        // Documents without text don't leave blank pages behind
+        if self.pages.last().is_some_and(Vec::is_empty) {
+            self.y = PAGE_HEIGHT - MARGIN;
+            return;
+        }
+
+        self.pages.push(Vec::new());
+        self.y = PAGE_HEIGHT - MARGIN;
+    }
+
+    fn push_block(&mut self, block: &TextBlock) {
+        let (font, font_size) = match block.heading {
+            Some(1) => (HEADING_FONT, 20.0),
+            Some(2) => (HEADING_FONT, 16.0),
+            Some(_) => (HEADING_FONT, 13.0),
+            None => (BODY_FONT, BODY_FONT_SIZE),
+        };
+        let line_height = font_size * LINE_SPACING;
+        let max_chars = ((PAGE_WIDTH - 2.0 * MARGIN) / (font_size * AVERAGE_GLYPH_WIDTH)) as usize;
+
+        for line in block
+            .text
+            .split('\n')
+            .flat_map(|line| wrap(line, max_chars))
+        {
+            if self.pages.is_empty() || self.y - line_height < MARGIN {
+                self.pages.push(Vec::new());
+                self.y = PAGE_HEIGHT - MARGIN;
+            }
+
+            self.y -= line_height;
+
+            if let Some(page) = self.pages.last_mut() {
+                page.extend([
+                    Operation::new("BT", vec![]),
+                    Operation::new("Tf", vec![font.into(), font_size.into()]),
+                    Operation::new("Td", vec![MARGIN.into(), self.y.into()]),
+                    Operation::new("Tj", vec![Object::string_literal(encode_win_ansi(&line))]),
+                    Operation::new("ET", vec![]),
+                ]);
+            }
+        }
+
+        // Paragraph spacing
+        self.y -= BODY_FONT_SIZE * (LINE_SPACING - 1.0) * 2.0;
+    }
+
+    fn into_document(self) -> Result<Document> {
+        let mut doc = Document::with_version("1.5");
+        let pages_id = doc.new_object_id();
+        let font = |base_font: &str| {
+            dictionary! {
+                "Type" => "Font",
+                "Subtype" => "Type1",
+                "BaseFont" => base_font,
+                "Encoding" => "WinAnsiEncoding",
+            }
+        };
+        let body_font_id = doc.add_object(font("Helvetica"));
+        let heading_font_id = doc.add_object(font("Helvetica-Bold"));
+        let resources_id = doc.add_object(dictionary! {
+            "Font" => dictionary! {
+                BODY_FONT => body_font_id,
+                HEADING_FONT => heading_font_id,
+            },
+        });
+        let mut pages = self.pages;
+
+        // Books without text still get a single blank page
+        if pages.is_empty() {
+            pages.push(Vec::new());
+        }
+
+        let mut kids = Vec::new();
+
+        for operations in pages {
+            let content = Content { operations };
+            let content_id = doc.add_object(Stream::new(Dictionary::new(), content.encode()?));
+            let page_id = doc.add_object(dictionary! {
+                "Type" => "Page",
+                "Parent" => pages_id,
+                "Contents" => content_id,
+            });
+
+            kids.push(Object::from(page_id));
+        }
+
+        let page_count = kids.len() as i64;
+
+        doc.objects.insert(
+            pages_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Kids" => kids,
+                "Count" => page_count,
+                "Resources" => resources_id,
+                "MediaBox" => vec![0.into(), 0.into(), PAGE_WIDTH.into(), PAGE_HEIGHT.into()],
+            }),
+        );
+
+        let catalog_id = doc.add_object(dictionary! {
+            "Type" => "Catalog",
+            "Pages" => pages_id,
+        });
+
+        doc.trailer.set("Root", catalog_id);
+        doc.compress();
+
+        Ok(doc)
+    }
+}
+
+/// Wraps `line` at word boundaries into lines of at most `max_chars`
+/// characters. Words longer than a line are kept whole.
+fn wrap(line: &str, max_chars: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+
+    for word in line.split_whitespace() {
+        if !current.is_empty() && current.chars().count() + 1 + word.chars().count() > max_chars {
+            lines.push(std::mem::take(&mut current));
+        }
+
+        if !current.is_empty() {
+            current.push(' ');
+        }
+
+        current.push_str(word);
+    }
+
+    if !current.is_empty() {
+        lines.push(current);
+    }
+
+    lines
+}
+
+/// Encodes `text` in the WinAnsiEncoding of the standard Type1 fonts
+fn encode_win_ansi(text: &str) -> Vec<u8> {
+    text.chars()
+        .map(|c| match c {
+            '\u{a0}' => b' ',
+            '\u{20}'..='\u{7e}' | '\u{a1}'..='\u{ff}' => c as u8,
+            '\u{2026}' => 0x85,
+            '\u{2018}' => 0x91,
+            '\u{2019}' => 0x92,
+            '\u{201c}' => 0x93,
+            '\u{201d}' => 0x94,
+            '\u{2022}' => 0x95,
+            '\u{2013}' => 0x96,
+            '\u{2014}' => 0x97,
+            _ => b'?',
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use anyhow::Result;
+
+    use super::EpubToPdf;
+    use crate::epub::test_util::{EpubBuilder, content_opf};
+    use crate::pdf::Pdf;
+
+    #[tokio::test]
+    async fn converts_spine_documents_into_pages() -> Result<()> {
+        let epub = EpubBuilder::new(&content_opf(
+            "<dc:title>Printed Book</dc:title>
+        <dc:creator>Jane Doe</dc:creator>
+        <dc:language>en</dc:language>
+        <dc:identifier id=\"BookId\">urn:uuid:1234</dc:identifier>",
+            &[
+                ("ch1", "ch1.xhtml", "application/xhtml+xml"),
+                ("ch2", "ch2.xhtml", "application/xhtml+xml"),
+            ],
+        ))
+        .entry(
+            "ch1.xhtml",
+            "<html><body><h1>Chapter One</h1><p>It was a dark night.</p></body></html>",
+        )
+        .entry("ch2.xhtml", "<html><body><p>The end.</p></body></html>")
+        .open()?;
+        let mut bytes = Vec::new();
+        EpubToPdf::new(&epub).convert().await?.save_to(&mut bytes)?;
+
+        let pdf = Pdf::from_reader(bytes.as_slice())?;
+        let metadata = pdf.metadata()?;
+        let pages = pdf
+            .pages_text()
+            .map(|(_, text)| text)
+            .collect::<Result<Vec<String>>>()?;
+
+        assert_eq!(metadata.title.as_deref(), Some("Printed Book"));
+        assert_eq!(metadata.author.as_deref(), Some("Jane Doe"));
+        assert_eq!(pdf.language().as_deref(), Some("en"));
+        assert_eq!(pages.len(), 2);
+        assert!(pages[0].contains("Chapter One"));
+        assert!(pages[0].contains("It was a dark night."));
+        assert!(pages[1].contains("The end."));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,5 @@
+mod epub_to_markdown;
+mod epub_to_pdf;
+
+pub use epub_to_markdown::EpubToMarkdown;
+pub use epub_to_pdf::EpubToPdf;
@@ -1,9 +1,11 @@
+use std::io::Read;
 use std::path::PathBuf;
 
 use anyhow::Result;
 use xml::{EventReader, reader::XmlEvent};
 
 pub const CONTAINER_XML: &str = "META-INF/container.xml";
+pub const OEBPS_PACKAGE_MEDIA_TYPE: &str = "application/oebps-package+xml";
 
 /// The `rootfile` element in the `META-INF/container.xml` file
 #[derive(Debug, Clone)]
@@ -21,9 +23,13 @@ pub struct MetaInfContainer {
 impl MetaInfContainer {
     /// Parses the `META-INF/container.xml` file and extracts the root files.
     pub fn new(container_xml: Vec<u8>) -> Result<Self> {
-        let xml_str = String::from_utf8(container_xml)
-            .map_err(|e| anyhow::anyhow!("Failed to convert bytes to string: {}", e))?;
-        let xml_reader = EventReader::from_str(&xml_str);
+        Self::from_reader(container_xml.as_slice())
+    }
+
+    /// Parses the `META-INF/container.xml` file straight from `reader`, e.g.
+    /// the archive entry, without buffering it first.
+    pub fn from_reader<R: Read>(reader: R) -> Result<Self> {
+        let xml_reader = EventReader::new(reader);
         let mut rootfiles: Vec<RootFile> = Vec::new();
 
         for maybe_event in xml_reader {
@@ -37,7 +43,7 @@ impl MetaInfContainer {
                     .iter()
                     .find(|attr| attr.name.local_name == "media-type")
                     .map_or_else(
-                        || "application/oebps-package+xml".to_string(),
+                        || OEBPS_PACKAGE_MEDIA_TYPE.to_string(),
                         |attr| attr.value.clone(),
                     );
 
@@ -55,6 +61,23 @@ impl MetaInfContainer {
 
         Ok(Self { rootfiles })
     }
+
+    /// Iterates over the root files declaring the provided media type.
+    pub fn rootfiles_by_media_type<'a>(
+        &'a self,
+        media_type: &'a str,
+    ) -> impl Iterator<Item = &'a RootFile> + 'a {
+        self.rootfiles
+            .iter()
+            .filter(move |rootfile| rootfile.media_type == media_type)
+    }
+
+    /// Returns the first root file pointing at an OEBPS package document
+    /// (the OPF file).
+    pub fn oebps_rootfile(&self) -> Option<&RootFile> {
+        self.rootfiles_by_media_type(OEBPS_PACKAGE_MEDIA_TYPE)
+            .next()
+    }
 }
 
 #[cfg(test)]
@@ -87,4 +110,29 @@ mod test {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn finds_oebps_rootfile_among_multiple_rootfiles() -> Result<()> {
+        const MULTIPLE_ROOTFILES_CONTAINER_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+    <rootfiles>
+        <rootfile full-path="OEBPS/book.pdf" media-type="application/pdf" />
+        <rootfile full-path="OEBPS/package.opf" media-type="application/oebps-package+xml" />
+    </rootfiles>
+</container>
+"#;
+
+        let container_xml_bytes = MULTIPLE_ROOTFILES_CONTAINER_XML.as_bytes().to_vec();
+        let mic = MetaInfContainer::new(container_xml_bytes)?;
+
+        assert_eq!(mic.rootfiles.len(), 2);
+        assert_eq!(
+            mic.oebps_rootfile()
+                .map(|rootfile| rootfile.full_path.clone()),
+            Some(PathBuf::from("OEBPS/package.opf"))
+        );
+        assert_eq!(mic.rootfiles_by_media_type("application/pdf").count(), 1);
+
+        Ok(())
+    }
 }
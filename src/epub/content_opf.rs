@@ -1,29 +1,229 @@
-use std::fs::File;
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{Read, Seek};
 
 use anyhow::{Result, bail};
+use serde::Serialize;
 use xml::reader::{EventReader, XmlEvent};
 use zip::ZipArchive;
 
-use crate::epub::MetaInfContainer;
+use crate::epub::container::OEBPS_PACKAGE_MEDIA_TYPE;
+use crate::epub::isbn::{is_valid_isbn, normalize_isbn};
+use crate::epub::{Direction, MetaInfContainer};
 
-#[derive(Debug, Default)]
+/// A `dc:creator` entry of the OPF metadata
+#[derive(Debug, Default, Serialize)]
+pub struct Creator {
+    pub name: String,
+    /// The `opf:role` attribute or EPUB3 `role` refinement, a MARC relator
+    /// code such as `aut` (author), `ill` (illustrator) or `trl` (translator)
+    pub role: Option<String>,
+    /// The `opf:file-as` attribute or EPUB3 `file-as` refinement, the name
+    /// used when sorting, e.g. "Tolkien, J.R.R."
+    pub file_as: Option<String>,
+}
+
+impl Creator {
+    /// Returns whether the creator is an author, which creators without a
+    /// declared role are assumed to be.
+    pub fn is_author(&self) -> bool {
+        self.role.as_deref().is_none_or(|role| role == "aut")
+    }
+
+    /// Returns the name to sort the creator by, which is the `opf:file-as`
+    /// value when declared. Otherwise the last token of the name is moved
+    /// first, so "Jane Doe" sorts as "Doe, Jane".
+    pub fn sort_name(&self) -> String {
+        if let Some(file_as) = &self.file_as {
+            return file_as.clone();
+        }
+
+        let name = self.name.trim();
+
+        if name.contains(',') {
+            return name.to_string();
+        }
+
+        match name.rsplit_once(char::is_whitespace) {
+            Some((first, last)) => format!("{}, {}", last, first.trim_end()),
+            None => name.to_string(),
+        }
+    }
+}
+
+/// A `dc:identifier` entry of the OPF metadata
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Identifier {
+    /// The `opf:scheme` attribute, e.g. `ISBN`, `DOI` or `UUID`. Identifiers
+    /// without it, as in EPUB3, take the namespace of an URN value, e.g.
+    /// `uuid` for `urn:uuid:...`
+    pub scheme: Option<String>,
+    pub value: String,
+    /// Whether the package `unique-identifier` attribute references it
+    pub is_unique_id: bool,
+}
+
+impl Identifier {
+    /// Returns the bare ISBN when the value is an ISBN-10 or ISBN-13 with a
+    /// valid check digit.
+    pub fn isbn(&self) -> Option<String> {
+        normalize_isbn(&self.value).filter(|isbn| is_valid_isbn(isbn))
+    }
+
+    /// Whether the identifier declares itself an ISBN, through its scheme or
+    /// an `urn:isbn:` value.
+    fn declares_isbn(&self) -> bool {
+        self.scheme
+            .as_deref()
+            .is_some_and(|scheme| scheme.eq_ignore_ascii_case("isbn"))
+            || self
+                .value
+                .get(..9)
+                .is_some_and(|prefix| prefix.eq_ignore_ascii_case("urn:isbn:"))
+    }
+}
+
+#[derive(Debug, Default, Serialize)]
 pub struct Metadata {
     pub title: String,
-    pub creator: String,
+    pub creators: Vec<Creator>,
     pub language: String,
     pub identifier: String,
+    /// Every `dc:identifier`, in document order
+    pub identifiers: Vec<Identifier>,
+    pub publisher: Option<String>,
+    pub description: Option<String>,
+    /// The first `dc:date`, usually the publication date
+    pub date: Option<String>,
+    /// The EPUB3 `dcterms:modified` date the book was last modified
+    pub modified: Option<String>,
+    /// The manifest id of the cover image declared by the EPUB2
+    /// `<meta name="cover">` element
+    pub cover: Option<String>,
+    pub rights: Option<String>,
+    /// Every `dc:subject`, in document order
+    pub subjects: Vec<String>,
+    /// Accessibility properties declared through `schema:*` `meta` elements,
+    /// as `(property, value)` pairs
+    pub accessibility: Vec<(String, String)>,
+}
+
+impl Metadata {
+    /// Returns the first author declared in the OPF, falling back to the
+    /// first `dc:creator` when none of them is an author.
+    pub fn creator(&self) -> Option<&Creator> {
+        self.authors().into_iter().next()
+    }
+
+    /// Returns the authors declared in the OPF, in document order, leaving
+    /// out illustrators, translators and other contributors. Every
+    /// `dc:creator` is returned when none of them is an author.
+    pub fn authors(&self) -> Vec<&Creator> {
+        let authors = self
+            .creators
+            .iter()
+            .filter(|creator| creator.is_author())
+            .collect::<Vec<&Creator>>();
+
+        if authors.is_empty() {
+            return self.creators.iter().collect();
+        }
+
+        authors
+    }
 }
 
+/// Media type of the EPUB2 `toc.ncx` navigation file
+pub const NCX_MEDIA_TYPE: &str = "application/x-dtbncx+xml";
+
 #[derive(Debug)]
 pub struct ManifestItem {
     pub id: String,
     pub href: String,
     pub media_type: String,
+    /// The space separated `properties` attribute, e.g. `nav` for the EPUB3
+    /// navigation document
+    pub properties: Option<String>,
+}
+
+impl ManifestItem {
+    /// Returns whether the `properties` attribute lists `property`
+    pub fn has_property(&self, property: &str) -> bool {
+        self.properties
+            .as_deref()
+            .is_some_and(|properties| properties.split_whitespace().any(|p| p == property))
+    }
 }
 
 #[derive(Debug)]
 pub struct SpineItem {
     pub idref: String,
+    /// Whether the document is part of the main reading order, `false` for
+    /// `linear="no"` items such as footnotes or answer keys
+    pub linear: bool,
+}
+
+/// The EPUB specification major version the package declares
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EpubVersion {
+    V2,
+    V3,
+    Unknown,
+}
+
+impl EpubVersion {
+    /// Parses the `version` attribute of the `package` element, e.g. `2.0`,
+    /// `2.0.1` or `3.3`.
+    pub fn from_attribute(value: &str) -> Self {
+        match value.trim().split('.').next() {
+            Some("2") => Self::V2,
+            Some("3") => Self::V3,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+impl fmt::Display for EpubVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::V2 => write!(f, "EPUB 2"),
+            Self::V3 => write!(f, "EPUB 3"),
+            Self::Unknown => write!(f, "Unknown"),
+        }
+    }
+}
+
+/// A structural problem found in the OPF manifest
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ManifestFinding {
+    /// Two or more manifest items share the same `id`, only the first one is
+    /// used when resolving the spine
+    DuplicateId { id: String, href: String },
+    /// Two different manifest items point at the same `href`
+    DuplicateHref {
+        href: String,
+        first_id: String,
+        id: String,
+    },
+}
+
+impl fmt::Display for ManifestFinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DuplicateId { id, href } => {
+                write!(
+                    f,
+                    "Duplicate manifest id '{}' (ignored item '{}')",
+                    id, href
+                )
+            }
+            Self::DuplicateHref { href, first_id, id } => write!(
+                f,
+                "Manifest items '{}' and '{}' share the href '{}'",
+                first_id, id, href
+            ),
+        }
+    }
 }
 
 /// ContentOpf represents the `content.opf` file in an EPUB archive.
@@ -31,27 +231,61 @@ pub struct SpineItem {
 /// `OEBPS/{ISBN}.opf`
 #[derive(Debug)]
 pub struct ContentOpf {
+    /// The `version` attribute of the `package` element
+    pub version: Option<String>,
+    /// The `unique-identifier` attribute of the `package` element, which
+    /// references the `id` of the book's canonical `dc:identifier`
+    pub unique_identifier: Option<String>,
     pub metadata: Metadata,
     pub manifest: Vec<ManifestItem>,
     pub spine: Vec<SpineItem>,
+    /// The `page-progression-direction` attribute of the `spine` element
+    pub page_progression_direction: Option<Direction>,
+}
+
+/// Whether the element holds the metadata fields, either `metadata` or the
+/// OEBPS 1.2 `dc-metadata` and `x-metadata` wrappers.
+fn is_metadata_container(element: &str) -> bool {
+    matches!(element, "metadata" | "dc-metadata" | "x-metadata")
 }
 
 impl ContentOpf {
     pub fn new(bytes: Vec<u8>) -> Result<ContentOpf> {
-        let xml_str = String::from_utf8(bytes)
-            .map_err(|e| anyhow::anyhow!("Failed to convert bytes to string: {}", e))?;
-        let xml_reader = EventReader::from_str(&xml_str);
+        Self::from_reader(bytes.as_slice())
+    }
+
+    /// Parses the OPF file straight from `reader`, e.g. the archive entry,
+    /// without buffering it first.
+    pub fn from_reader<R: Read>(reader: R) -> Result<ContentOpf> {
+        let xml_reader = EventReader::new(reader);
 
         let mut content_opf = ContentOpf {
+            version: None,
+            unique_identifier: None,
             metadata: Metadata::default(),
             manifest: Vec::new(),
             spine: Vec::new(),
+            page_progression_direction: None,
         };
 
-        let mut current_element = String::new();
+        // Open elements, innermost last, and the character data collected for
+        // the `metadata` child being parsed
+        let mut elements: Vec<String> = Vec::new();
+        let mut text = String::new();
         let mut in_metadata = false;
         let mut in_manifest = false;
         let mut in_spine = false;
+        let mut identifier_id: Option<String> = None;
+        let mut identifier_scheme: Option<String> = None;
+        let mut has_primary_identifier = false;
+        let mut meta_property: Option<String> = None;
+        // Every `dc:title` with its `id`, and the EPUB3 `meta` elements
+        // refining another element as `(id, property, value)`
+        let mut titles: Vec<(Option<String>, String)> = Vec::new();
+        let mut creator_ids: Vec<Option<String>> = Vec::new();
+        let mut title_id: Option<String> = None;
+        let mut refines: Vec<(String, String, String)> = Vec::new();
+        let mut meta_refines: Option<(String, String)> = None;
 
         for event in xml_reader {
             match event? {
@@ -60,15 +294,41 @@ impl ContentOpf {
                 } => {
                     let element_name = name.local_name;
 
+                    if in_metadata
+                        && elements
+                            .last()
+                            .is_some_and(|parent| is_metadata_container(parent))
+                    {
+                        text.clear();
+                    }
+
                     match element_name.as_str() {
+                        "package" => {
+                            for attr in attributes {
+                                match attr.name.local_name.as_str() {
+                                    "version" => content_opf.version = Some(attr.value),
+                                    "unique-identifier" => {
+                                        content_opf.unique_identifier = Some(attr.value)
+                                    }
+                                    _ => {}
+                                }
+                            }
+                        }
                         "metadata" => in_metadata = true,
                         "manifest" => in_manifest = true,
-                        "spine" => in_spine = true,
+                        "spine" => {
+                            in_spine = true;
+                            content_opf.page_progression_direction = attributes
+                                .into_iter()
+                                .find(|attr| attr.name.local_name == "page-progression-direction")
+                                .and_then(|attr| Direction::from_attribute(&attr.value));
+                        }
                         "item" if in_manifest => {
                             let mut item = ManifestItem {
                                 id: String::new(),
                                 href: String::new(),
                                 media_type: String::new(),
+                                properties: None,
                             };
 
                             for attr in attributes {
@@ -76,6 +336,7 @@ impl ContentOpf {
                                     "id" => item.id = attr.value,
                                     "href" => item.href = attr.value,
                                     "media-type" => item.media_type = attr.value,
+                                    "properties" => item.properties = Some(attr.value),
                                     _ => {}
                                 }
                             }
@@ -83,53 +344,325 @@ impl ContentOpf {
                             content_opf.manifest.push(item);
                         }
                         "itemref" if in_spine => {
+                            let mut idref = None;
+                            let mut linear = true;
+
                             for attr in attributes {
-                                if attr.name.local_name == "idref" {
-                                    content_opf.spine.push(SpineItem { idref: attr.value });
+                                match attr.name.local_name.as_str() {
+                                    "idref" => idref = Some(attr.value),
+                                    "linear" => linear = attr.value.trim() != "no",
+                                    _ => {}
                                 }
                             }
+
+                            if let Some(idref) = idref {
+                                content_opf.spine.push(SpineItem { idref, linear });
+                            }
                         }
                         _ => {
-                            current_element = element_name;
+                            if element_name == "creator" && in_metadata {
+                                let find_attr = |local_name: &str| {
+                                    attributes
+                                        .iter()
+                                        .find(|attr| attr.name.local_name == local_name)
+                                        .map(|attr| attr.value.clone())
+                                };
+
+                                content_opf.metadata.creators.push(Creator {
+                                    name: String::new(),
+                                    role: find_attr("role"),
+                                    file_as: find_attr("file-as"),
+                                });
+                                creator_ids.push(find_attr("id"));
+                            } else if element_name == "identifier" {
+                                identifier_id = None;
+                                identifier_scheme = None;
+
+                                for attr in attributes {
+                                    match attr.name.local_name.as_str() {
+                                        "id" => identifier_id = Some(attr.value),
+                                        "scheme" => identifier_scheme = Some(attr.value),
+                                        _ => {}
+                                    }
+                                }
+                            } else if element_name == "title" && in_metadata {
+                                title_id = attributes
+                                    .into_iter()
+                                    .find(|attr| attr.name.local_name == "id")
+                                    .map(|attr| attr.value);
+                            } else if element_name == "meta" && in_metadata {
+                                let find_attr = |local_name: &str| {
+                                    attributes
+                                        .iter()
+                                        .find(|attr| attr.name.local_name == local_name)
+                                        .map(|attr| attr.value.clone())
+                                };
+
+                                meta_refines = find_attr("refines").zip(find_attr("property"));
+
+                                // EPUB3 declares the value as character data while
+                                // EPUB2 uses the `name` and `content` attributes
+                                meta_property = find_attr("property");
+
+                                if let (Some(name), Some(content)) =
+                                    (find_attr("name"), find_attr("content"))
+                                {
+                                    if name.starts_with("schema:") {
+                                        content_opf.metadata.accessibility.push((name, content));
+                                    } else if name == "cover" {
+                                        content_opf.metadata.cover = Some(content);
+                                    }
+                                }
+                            }
                         }
                     }
+
+                    elements.push(element_name);
                 }
-                XmlEvent::EndElement { name } => match name.local_name.as_str() {
-                    "metadata" => in_metadata = false,
-                    "manifest" => in_manifest = false,
-                    "spine" => in_spine = false,
-                    _ => {}
-                },
-                XmlEvent::Characters(text) => {
-                    if in_metadata {
-                        match current_element.as_str() {
-                            "title" => content_opf.metadata.title = text,
-                            "creator" => content_opf.metadata.creator = text,
-                            "language" => content_opf.metadata.language = text,
-                            "identifier" => content_opf.metadata.identifier = text,
-                            _ => {}
+                XmlEvent::EndElement { name } => {
+                    elements.pop();
+
+                    match name.local_name.as_str() {
+                        "metadata" => in_metadata = false,
+                        "manifest" => in_manifest = false,
+                        "spine" => in_spine = false,
+                        // Character data is only assigned once a metadata field
+                        // closes, so text around nested or sibling elements never
+                        // lands in the wrong field
+                        element_name
+                            if in_metadata
+                                && elements
+                                    .last()
+                                    .is_some_and(|parent| is_metadata_container(parent)) =>
+                        {
+                            let text = std::mem::take(&mut text);
+
+                            match element_name {
+                                "title" => titles.push((title_id.take(), text)),
+                                "creator" => {
+                                    if let Some(creator) = content_opf.metadata.creators.last_mut()
+                                    {
+                                        creator.name = text;
+                                    }
+                                }
+                                "language" => content_opf.metadata.language = text,
+                                "publisher" => content_opf.metadata.publisher = Some(text),
+                                "description" => content_opf.metadata.description = Some(text),
+                                "date" if content_opf.metadata.date.is_none() => {
+                                    content_opf.metadata.date = Some(text)
+                                }
+                                "rights" => content_opf.metadata.rights = Some(text),
+                                "subject" => content_opf.metadata.subjects.push(text),
+                                "identifier" => {
+                                    let is_unique_id = content_opf.unique_identifier.is_some()
+                                        && identifier_id == content_opf.unique_identifier;
+
+                                    if !has_primary_identifier {
+                                        has_primary_identifier = is_unique_id;
+                                        content_opf.metadata.identifier = text.clone();
+                                    }
+
+                                    content_opf.metadata.identifiers.push(Identifier {
+                                        scheme: identifier_scheme.take().or_else(|| {
+                                            text.strip_prefix("urn:")
+                                                .and_then(|urn| urn.split_once(':'))
+                                                .map(|(namespace, _)| namespace.to_string())
+                                        }),
+                                        value: text,
+                                        is_unique_id,
+                                    });
+                                }
+                                "meta" => {
+                                    if let Some((id, property)) = meta_refines.take() {
+                                        refines.push((
+                                            id.trim_start_matches('#').to_string(),
+                                            property,
+                                            text.trim().to_string(),
+                                        ));
+                                    } else if let Some(property) = meta_property.take() {
+                                        if property.starts_with("schema:") {
+                                            content_opf
+                                                .metadata
+                                                .accessibility
+                                                .push((property, text));
+                                        } else if property == "dcterms:modified" {
+                                            content_opf.metadata.modified =
+                                                Some(text.trim().to_string());
+                                        }
+                                    }
+                                }
+                                _ => {}
+                            }
                         }
+                        _ => {}
                     }
                 }
+                XmlEvent::Characters(data) | XmlEvent::CData(data) if in_metadata => {
+                    text.push_str(&data);
+                }
+                _ => {}
+            }
+        }
+
+        // EPUB3 declares the creator role and sort name in `meta` elements
+        // refining it, the EPUB2 attributes win when both are present
+        for (id, property, value) in &refines {
+            let Some(creator) = creator_ids
+                .iter()
+                .position(|creator_id| creator_id.as_ref() == Some(id))
+                .and_then(|index| content_opf.metadata.creators.get_mut(index))
+            else {
+                continue;
+            };
+
+            match property.as_str() {
+                "role" => {
+                    creator.role.get_or_insert_with(|| value.clone());
+                }
+                "file-as" => {
+                    creator.file_as.get_or_insert_with(|| value.clone());
+                }
                 _ => {}
             }
         }
 
+        // The title refined as the `main` one, otherwise the first declared,
+        // so subtitles and collection titles never replace it
+        let main_title = titles.iter().position(|(id, _)| {
+            id.as_deref().is_some_and(|id| {
+                refines.iter().any(|(refined_id, property, value)| {
+                    refined_id == id && property == "title-type" && value == "main"
+                })
+            })
+        });
+
+        if !titles.is_empty() {
+            content_opf.metadata.title = titles.swap_remove(main_title.unwrap_or(0)).1;
+        }
+
         Ok(content_opf)
     }
 
-    pub fn resolve_opf_file(zip: &mut ZipArchive<File>, mic: &MetaInfContainer) -> Result<String> {
+    /// Returns the EPUB version declared by the `package` element
+    pub fn epub_version(&self) -> EpubVersion {
+        self.version
+            .as_deref()
+            .map_or(EpubVersion::Unknown, EpubVersion::from_attribute)
+    }
+
+    /// Finds the `toc.ncx` manifest item
+    pub fn ncx_item(&self) -> Option<&ManifestItem> {
+        self.manifest
+            .iter()
+            .find(|item| item.media_type == NCX_MEDIA_TYPE)
+    }
+
+    /// Finds the EPUB3 navigation document, the manifest item with the `nav`
+    /// property
+    pub fn nav_item(&self) -> Option<&ManifestItem> {
+        self.manifest.iter().find(|item| item.has_property("nav"))
+    }
+
+    /// Finds the cover image, the manifest item with the EPUB3 `cover-image`
+    /// property or the one referenced by the EPUB2 `<meta name="cover">`
+    pub fn cover_item(&self) -> Option<&ManifestItem> {
+        self.manifest
+            .iter()
+            .find(|item| item.has_property("cover-image"))
+            .or_else(|| {
+                self.metadata
+                    .cover
+                    .as_deref()
+                    .and_then(|id| self.manifest_item(id))
+            })
+    }
+
+    /// Finds the manifest item with the provided `id`. When the manifest holds
+    /// duplicated ids, the first declared item wins.
+    pub fn manifest_item(&self, id: &str) -> Option<&ManifestItem> {
+        self.manifest.iter().find(|item| item.id == id)
+    }
+
+    /// Reports manifest items declaring an already used `id`, or an `href`
+    /// already referenced by another item.
+    pub fn manifest_findings(&self) -> Vec<ManifestFinding> {
+        let mut findings = Vec::new();
+        let mut ids: HashMap<&str, &str> = HashMap::new();
+        let mut hrefs: HashMap<&str, &str> = HashMap::new();
+
+        for item in &self.manifest {
+            if ids.contains_key(item.id.as_str()) {
+                findings.push(ManifestFinding::DuplicateId {
+                    id: item.id.clone(),
+                    href: item.href.clone(),
+                });
+                continue;
+            }
+
+            ids.insert(&item.id, &item.href);
+
+            match hrefs.get(item.href.as_str()) {
+                Some(first_id) => findings.push(ManifestFinding::DuplicateHref {
+                    href: item.href.clone(),
+                    first_id: first_id.to_string(),
+                    id: item.id.clone(),
+                }),
+                None => {
+                    hrefs.insert(&item.href, &item.id);
+                }
+            }
+        }
+
+        findings
+    }
+
+    /// Returns the first identifier holding a valid ISBN, preferring the ones
+    /// declared as such through their scheme or an `urn:isbn:` value.
+    pub fn isbn(&self) -> Option<String> {
+        let identifiers = &self.metadata.identifiers;
+
+        identifiers
+            .iter()
+            .filter(|identifier| identifier.declares_isbn())
+            .chain(identifiers)
+            .find_map(Identifier::isbn)
+    }
+
+    /// Returns the `dc:identifier` referenced by the package `unique-identifier`
+    /// attribute, or the last identifier found when the reference is missing.
+    pub fn primary_identifier(&self) -> &str {
+        &self.metadata.identifier
+    }
+
+    /// Resolves the path of the OPF file in the archive. The container root
+    /// files are authoritative: OEBPS package root files come first, then any
+    /// other root file pointing at an `.opf` file. The well-known paths are only
+    /// tried when none of the root files exists in the archive.
+    pub fn resolve_opf_file<R: Read + Seek>(
+        zip: &mut ZipArchive<R>,
+        mic: &MetaInfContainer,
+    ) -> Result<String> {
         const TOP_LEVEL_OPF_PATH: &str = "content.opf";
         const DEFAULT_OPF_PATH: &str = "OEBPS/content.opf";
         const ALTERNATIVE_OPF_PATH: &str = "OPS/content.opf";
 
-        let opf_path = mic.rootfiles[0].full_path.to_str();
+        let other_opf_rootfiles = mic.rootfiles.iter().filter(|rootfile| {
+            rootfile.media_type != OEBPS_PACKAGE_MEDIA_TYPE
+                && rootfile
+                    .full_path
+                    .extension()
+                    .is_some_and(|extension| extension.eq_ignore_ascii_case("opf"))
+        });
 
-        if let Some(opf_path) = opf_path
-            && opf_path.ends_with("opf")
-            && zip.by_name(opf_path).is_ok()
+        for rootfile in mic
+            .rootfiles_by_media_type(OEBPS_PACKAGE_MEDIA_TYPE)
+            .chain(other_opf_rootfiles)
         {
-            return Ok(opf_path.to_string());
+            if let Some(opf_path) = rootfile.full_path.to_str()
+                && zip.by_name(opf_path).is_ok()
+            {
+                return Ok(opf_path.to_string());
+            }
         }
 
         if zip.by_name(DEFAULT_OPF_PATH).is_ok() {
@@ -147,3 +680,350 @@ impl ContentOpf {
         bail!("Failed to resolve OPF file path")
     }
 }
+
+#[cfg(test)]
+mod test {
+    use anyhow::Result;
+
+    use super::{ContentOpf, Creator, EpubVersion, ManifestFinding};
+
+    const CONTENT_OPF: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="2.0" unique-identifier="BookId">
+    <metadata xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:opf="http://www.idpf.org/2007/opf">
+        <dc:title>The Rust Programming Language</dc:title>
+        <dc:creator opf:role="aut" opf:file-as="Klabnik, Steve">Steve Klabnik</dc:creator>
+        <dc:language>en</dc:language>
+        <dc:publisher>No Starch Press</dc:publisher>
+        <dc:description>The official book on the Rust programming language.</dc:description>
+        <dc:date opf:event="publication">2023-02-28</dc:date>
+        <dc:date opf:event="modification">2023-03-01</dc:date>
+        <dc:rights>All rights reserved</dc:rights>
+        <dc:subject>Programming</dc:subject>
+        <dc:subject>Rust</dc:subject>
+        <dc:identifier id="BookId" opf:scheme="ISBN">9781718500457</dc:identifier>
+        <dc:identifier opf:scheme="UUID">urn:uuid:0a2b6e6c-6a6b-4d7e-9f8e-1c2d3e4f5a6b</dc:identifier>
+        <meta property="schema:accessMode">textual</meta>
+        <meta name="schema:accessibilityFeature" content="tableOfContents"/>
+    </metadata>
+    <manifest>
+        <item id="ncx" href="toc.ncx" media-type="application/x-dtbncx+xml"/>
+        <item id="chapter01" href="Text/chapter01.xhtml" media-type="application/xhtml+xml"/>
+    </manifest>
+    <spine toc="ncx">
+        <itemref idref="chapter01"/>
+    </spine>
+</package>
+"#;
+
+    #[tokio::test]
+    async fn resolves_primary_identifier_from_unique_identifier() -> Result<()> {
+        let content_opf = ContentOpf::new(CONTENT_OPF.as_bytes().to_vec())?;
+
+        assert_eq!(content_opf.unique_identifier.as_deref(), Some("BookId"));
+        assert_eq!(content_opf.epub_version(), EpubVersion::V2);
+        assert_eq!(content_opf.primary_identifier(), "9781718500457");
+        assert_eq!(content_opf.manifest.len(), 2);
+        assert_eq!(content_opf.spine.len(), 1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn finds_the_cover_image() -> Result<()> {
+        const EPUB2_OPF: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="2.0">
+    <metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+        <meta name="cover" content="cover-jpg"/>
+    </metadata>
+    <manifest>
+        <item id="ch1" href="ch1.xhtml" media-type="application/xhtml+xml"/>
+        <item id="cover-jpg" href="cover.jpg" media-type="image/jpeg"/>
+    </manifest>
+</package>
+"#;
+        let content_opf = ContentOpf::new(EPUB2_OPF.as_bytes().to_vec())?;
+
+        assert_eq!(
+            content_opf.cover_item().map(|item| item.href.as_str()),
+            Some("cover.jpg")
+        );
+
+        let content_opf = ContentOpf::new(
+            EPUB2_OPF
+                .replace(r#"<meta name="cover" content="cover-jpg"/>"#, "")
+                .into_bytes(),
+        )?;
+
+        assert!(content_opf.cover_item().is_none());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn finds_isbn_among_identifiers() -> Result<()> {
+        const IDENTIFIERS_OPF: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="2.0" unique-identifier="uuid">
+    <metadata xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:opf="http://www.idpf.org/2007/opf">
+        <dc:identifier id="uuid" opf:scheme="UUID">urn:uuid:0a2b6e6c-6a6b-4d7e-9f8e-1c2d3e4f5a6b</dc:identifier>
+        <dc:identifier opf:scheme="calibre">1234567890</dc:identifier>
+        <dc:identifier opf:scheme="ISBN">978-1-7185-0045-8</dc:identifier>
+        <dc:identifier opf:scheme="ISBN">0-306-40615-2</dc:identifier>
+    </metadata>
+</package>
+"#;
+
+        let content_opf = ContentOpf::new(IDENTIFIERS_OPF.as_bytes().to_vec())?;
+        let identifiers = &content_opf.metadata.identifiers;
+
+        assert_eq!(identifiers.len(), 4);
+        assert!(identifiers[0].is_unique_id);
+        assert_eq!(identifiers[0].scheme.as_deref(), Some("UUID"));
+        // Neither the calibre id nor the first ISBN have a valid check digit
+        assert_eq!(identifiers[1].isbn(), None);
+        assert_eq!(identifiers[2].isbn(), None);
+        assert_eq!(content_opf.isbn().as_deref(), Some("0306406152"));
+        assert_eq!(
+            ContentOpf::new(CONTENT_OPF.as_bytes().to_vec())?
+                .isbn()
+                .as_deref(),
+            Some("9781718500457")
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn collects_dublin_core_metadata() -> Result<()> {
+        let content_opf = ContentOpf::new(CONTENT_OPF.as_bytes().to_vec())?;
+        let metadata = &content_opf.metadata;
+
+        assert_eq!(metadata.publisher.as_deref(), Some("No Starch Press"));
+        assert_eq!(
+            metadata.description.as_deref(),
+            Some("The official book on the Rust programming language.")
+        );
+        assert_eq!(metadata.date.as_deref(), Some("2023-02-28"));
+        assert_eq!(metadata.rights.as_deref(), Some("All rights reserved"));
+        assert_eq!(metadata.subjects, vec!["Programming", "Rust"]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn collects_accessibility_metadata() -> Result<()> {
+        let content_opf = ContentOpf::new(CONTENT_OPF.as_bytes().to_vec())?;
+
+        assert_eq!(
+            content_opf.metadata.accessibility,
+            vec![
+                ("schema:accessMode".to_string(), "textual".to_string()),
+                (
+                    "schema:accessibilityFeature".to_string(),
+                    "tableOfContents".to_string()
+                ),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn sorts_creators_by_file_as() -> Result<()> {
+        let content_opf = ContentOpf::new(CONTENT_OPF.as_bytes().to_vec())?;
+        let creator = Creator {
+            name: "J. R. R. Tolkien".to_string(),
+            role: None,
+            file_as: None,
+        };
+
+        assert_eq!(
+            content_opf
+                .metadata
+                .creator()
+                .map(Creator::sort_name)
+                .as_deref(),
+            Some("Klabnik, Steve")
+        );
+        assert_eq!(creator.sort_name(), "Tolkien, J. R. R.");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn parses_multiple_creators_with_roles() -> Result<()> {
+        const CREATORS_OPF: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="2.0">
+    <metadata xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:opf="http://www.idpf.org/2007/opf">
+        <dc:title>The Hobbit</dc:title>
+        <dc:creator opf:role="ill" opf:file-as="Lee, Alan">Alan Lee</dc:creator>
+        <dc:creator opf:role="aut" opf:file-as="Tolkien, J.R.R.">J.R.R. Tolkien</dc:creator>
+    </metadata>
+</package>
+"#;
+
+        let content_opf = ContentOpf::new(CREATORS_OPF.as_bytes().to_vec())?;
+        let creators = &content_opf.metadata.creators;
+
+        assert_eq!(creators.len(), 2);
+        assert_eq!(creators[0].name, "Alan Lee");
+        assert_eq!(creators[0].role.as_deref(), Some("ill"));
+        assert_eq!(creators[1].name, "J.R.R. Tolkien");
+        assert_eq!(creators[1].role.as_deref(), Some("aut"));
+        assert_eq!(creators[1].file_as.as_deref(), Some("Tolkien, J.R.R."));
+        assert_eq!(
+            content_opf
+                .metadata
+                .creator()
+                .map(|creator| creator.name.as_str()),
+            Some("J.R.R. Tolkien")
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn lists_authors_without_other_contributors() -> Result<()> {
+        const CREATORS_OPF: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="2.0">
+    <metadata xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:opf="http://www.idpf.org/2007/opf">
+        <dc:creator opf:role="aut">Terry Pratchett</dc:creator>
+        <dc:creator opf:role="ill">Paul Kidby</dc:creator>
+        <dc:creator>Neil Gaiman</dc:creator>
+        <dc:creator opf:role="trl">Jane Doe</dc:creator>
+    </metadata>
+</package>
+"#;
+        let names = |opf: &str| -> Result<Vec<String>> {
+            Ok(ContentOpf::new(opf.as_bytes().to_vec())?
+                .metadata
+                .authors()
+                .into_iter()
+                .map(|creator| creator.name.clone())
+                .collect())
+        };
+
+        assert_eq!(names(CREATORS_OPF)?, ["Terry Pratchett", "Neil Gaiman"]);
+        assert_eq!(
+            names(
+                &CREATORS_OPF
+                    .replace(r#"opf:role="aut""#, r#"opf:role="edt""#)
+                    .replace("<dc:creator>", r#"<dc:creator opf:role="edt">"#)
+            )?,
+            ["Terry Pratchett", "Paul Kidby", "Neil Gaiman", "Jane Doe"]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn keeps_metadata_text_within_its_element() -> Result<()> {
+        const EPUB3_OPF: &str = r##"<?xml version="1.0" encoding="UTF-8"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="3.0" unique-identifier="pub-id">
+    <metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+        <dc:identifier id="pub-id">urn:isbn:9780441172719</dc:identifier>
+        <dc:title id="title">Dune</dc:title>
+        <meta refines="#title" property="title-type">main</meta>
+        <dc:title id="subtitle">Deluxe Edition</dc:title>
+        <meta refines="#subtitle" property="title-type">subtitle</meta>
+        <dc:creator id="creator">Frank Herbert</dc:creator>
+        <meta refines="#creator" property="role" scheme="marc:relators">aut</meta>
+        <meta refines="#creator" property="file-as">Herbert, Frank</meta>
+        <dc:description>A <em>desert</em> planet<br/> saga</dc:description>
+        <meta property="dcterms:modified">2019-10-01T00:00:00Z</meta>
+        <dc:language>en</dc:language>
+        stray text
+        <meta property="schema:accessMode">textual</meta>
+    </metadata>
+</package>
+"##;
+
+        let content_opf = ContentOpf::new(EPUB3_OPF.as_bytes().to_vec())?;
+        let metadata = &content_opf.metadata;
+
+        assert_eq!(metadata.title, "Dune");
+        assert_eq!(metadata.creators.len(), 1);
+        assert_eq!(metadata.creators[0].name, "Frank Herbert");
+        assert_eq!(metadata.creators[0].role.as_deref(), Some("aut"));
+        assert_eq!(
+            metadata.creators[0].file_as.as_deref(),
+            Some("Herbert, Frank")
+        );
+        assert!(metadata.creators[0].is_author());
+        assert_eq!(
+            metadata.description.as_deref(),
+            Some("A desert planet saga")
+        );
+        assert_eq!(metadata.modified.as_deref(), Some("2019-10-01T00:00:00Z"));
+        assert_eq!(metadata.language, "en");
+        assert_eq!(metadata.identifier, "urn:isbn:9780441172719");
+        assert_eq!(metadata.identifiers[0].scheme.as_deref(), Some("isbn"));
+        assert!(metadata.identifiers[0].is_unique_id);
+        assert_eq!(
+            metadata.accessibility,
+            vec![("schema:accessMode".to_string(), "textual".to_string())]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn prefers_the_title_refined_as_main() -> Result<()> {
+        const TITLES_OPF: &str = r##"<?xml version="1.0" encoding="UTF-8"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="3.0">
+    <metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+        <dc:title id="collection">The Dune Chronicles</dc:title>
+        <meta refines="#collection" property="title-type">collection</meta>
+        <dc:title id="main">Children of Dune</dc:title>
+        <meta refines="#main" property="title-type">main</meta>
+    </metadata>
+</package>
+"##;
+
+        let content_opf = ContentOpf::new(TITLES_OPF.as_bytes().to_vec())?;
+
+        assert_eq!(content_opf.metadata.title, "Children of Dune");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn detects_duplicated_manifest_ids_and_hrefs() -> Result<()> {
+        const DUPLICATES_OPF: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="2.0">
+    <manifest>
+        <item id="chapter01" href="Text/chapter01.xhtml" media-type="application/xhtml+xml"/>
+        <item id="chapter01" href="Text/chapter01-copy.xhtml" media-type="application/xhtml+xml"/>
+        <item id="chapter01-alt" href="Text/chapter01.xhtml" media-type="application/xhtml+xml"/>
+    </manifest>
+    <spine>
+        <itemref idref="chapter01"/>
+    </spine>
+</package>
+"#;
+
+        let content_opf = ContentOpf::new(DUPLICATES_OPF.as_bytes().to_vec())?;
+
+        assert_eq!(
+            content_opf.manifest_findings(),
+            vec![
+                ManifestFinding::DuplicateId {
+                    id: "chapter01".to_string(),
+                    href: "Text/chapter01-copy.xhtml".to_string(),
+                },
+                ManifestFinding::DuplicateHref {
+                    href: "Text/chapter01.xhtml".to_string(),
+                    first_id: "chapter01".to_string(),
+                    id: "chapter01-alt".to_string(),
+                },
+            ]
+        );
+        assert_eq!(
+            content_opf
+                .manifest_item("chapter01")
+                .map(|item| item.href.as_str()),
+            Some("Text/chapter01.xhtml")
+        );
+
+        Ok(())
+    }
+}
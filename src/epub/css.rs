@@ -0,0 +1,182 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fs::{File, copy, create_dir_all, read_to_string, write};
+use std::io;
+use std::path::Path;
+
+use anyhow::{Result, bail};
+
+use crate::epub::Epub;
+use crate::util::zip::{get_file_reader, safe_join};
+
+/// Name of the file mapping each extracted stylesheet to its archive path
+pub const CSS_MAP_FILE: &str = "css-map.json";
+
+const CSS_MEDIA_TYPE: &str = "text/css";
+
+impl Epub {
+    /// Copies every stylesheet in the manifest into the flat `outdir`, along
+    /// with a [`CSS_MAP_FILE`] mapping each file name to its archive path.
+    ///
+    /// Stylesheets sharing a file name get a numeric suffix, e.g.
+    /// `style-2.css`. Returns the `(file name, archive path)` pairs written.
+    pub async fn extract_css<P: AsRef<Path>>(&self, outdir: P) -> Result<Vec<(String, String)>> {
+        let outdir = outdir.as_ref();
+        let content_opf = self.content_opf().await?;
+        let mut archive = self.archive.lock().await;
+        let mut file_names = HashSet::new();
+        let mut mapping = BTreeMap::new();
+        let mut extracted = Vec::new();
+
+        create_dir_all(outdir)?;
+
+        for item in &content_opf.manifest {
+            if item.media_type != CSS_MEDIA_TYPE {
+                continue;
+            }
+
+            let path = self.resolve_href(&item.href);
+            let file_name = Path::new(&path)
+                .file_name()
+                .and_then(|name| name.to_str())
+                .unwrap_or("style.css");
+            let (stem, extension) = file_name.rsplit_once('.').unwrap_or((file_name, "css"));
+            let mut flat_name = file_name.to_string();
+            let mut suffix = 1;
+
+            while !file_names.insert(flat_name.clone()) {
+                suffix += 1;
+                flat_name = format!("{}-{}.{}", stem, suffix, extension);
+            }
+
+            io::copy(
+                &mut get_file_reader(&mut archive, &path)?,
+                &mut File::create(outdir.join(&flat_name))?,
+            )?;
+            mapping.insert(flat_name.clone(), path.clone());
+            extracted.push((flat_name, path));
+        }
+
+        write(
+            outdir.join(CSS_MAP_FILE),
+            serde_json::to_string_pretty(&mapping)?,
+        )?;
+
+        Ok(extracted)
+    }
+}
+
+/// Copies the stylesheets extracted with [`Epub::extract_css`] from `css_dir`
+/// back to their original paths in the unpackaged `source` directory.
+/// Returns the archive paths written.
+///
+/// The [`CSS_MAP_FILE`] is editable, so names that aren't plain file names
+/// within `css_dir` are rejected.
+pub fn inject_css<P: AsRef<Path>>(css_dir: P, source: P) -> Result<Vec<String>> {
+    let css_dir = css_dir.as_ref();
+    let source = source.as_ref();
+    let mapping: BTreeMap<String, String> =
+        serde_json::from_str(&read_to_string(css_dir.join(CSS_MAP_FILE))?)?;
+    let mut injected = Vec::new();
+
+    for (flat_name, path) in mapping {
+        if matches!(flat_name.as_str(), "" | "." | "..")
+            || flat_name.contains(['/', '\\', ':', '\0'])
+        {
+            bail!(
+                "Refusing to read '{}' outside of '{}', {} must only list file names",
+                flat_name,
+                css_dir.display(),
+                CSS_MAP_FILE
+            );
+        }
+
+        copy(css_dir.join(&flat_name), safe_join(source, &path)?)?;
+        injected.push(path);
+    }
+
+    Ok(injected)
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+    use std::fs::{read_to_string, remove_dir_all, write};
+    use std::io::Cursor;
+
+    use anyhow::Result;
+    use zip::ZipArchive;
+
+    use super::{CSS_MAP_FILE, inject_css};
+    use crate::epub::extract_archive;
+    use crate::epub::test_util::{EpubBuilder, content_opf};
+
+    #[tokio::test]
+    async fn round_trips_edited_stylesheets() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("bookworm-css-{}", std::process::id()));
+        let css_dir = dir.join("css");
+        let source = dir.join("source");
+        let builder = EpubBuilder::new(&content_opf(
+            r#"<dc:identifier id="BookId">urn:uuid:1234</dc:identifier>"#,
+            &[
+                ("ch1", "Text/ch1.xhtml", "application/xhtml+xml"),
+                ("style", "Styles/style.css", "text/css"),
+                ("extra", "Extra/style.css", "text/css"),
+            ],
+        ))
+        .entry("Text/ch1.xhtml", "<html/>")
+        .entry("Styles/style.css", "p { margin: 0 }")
+        .entry("Extra/style.css", "h1 { color: red }");
+
+        extract_archive(
+            &mut ZipArchive::new(Cursor::new(builder.build()?))?,
+            &source,
+        )?;
+
+        let extracted = builder.open()?.extract_css(&css_dir).await?;
+
+        assert_eq!(
+            extracted,
+            [
+                ("style.css".to_string(), "Styles/style.css".to_string()),
+                ("style-2.css".to_string(), "Extra/style.css".to_string()),
+            ]
+        );
+        assert_eq!(
+            read_to_string(css_dir.join("style-2.css"))?,
+            "h1 { color: red }"
+        );
+
+        write(css_dir.join("style-2.css"), "h1 { color: blue }")?;
+
+        assert_eq!(
+            inject_css(&css_dir, &source)?,
+            ["Extra/style.css", "Styles/style.css"]
+        );
+        assert_eq!(
+            read_to_string(source.join("Extra/style.css"))?,
+            "h1 { color: blue }"
+        );
+        assert_eq!(
+            read_to_string(source.join("Styles/style.css"))?,
+            "p { margin: 0 }"
+        );
+
+        for flat_name in ["../../source/Text/ch1.xhtml", "..", "sub\\style.css"] {
+            write(
+                css_dir.join(CSS_MAP_FILE),
+                serde_json::to_string(&BTreeMap::from([(flat_name, "Styles/style.css")]))?,
+            )?;
+
+            assert!(inject_css(&css_dir, &source).is_err());
+        }
+
+        assert_eq!(
+            read_to_string(source.join("Styles/style.css"))?,
+            "p { margin: 0 }"
+        );
+
+        remove_dir_all(dir)?;
+
+        Ok(())
+    }
+}
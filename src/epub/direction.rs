@@ -0,0 +1,210 @@
+use std::fmt;
+
+use anyhow::Result;
+
+use crate::epub::Epub;
+use crate::epub::text::html_to_text;
+use crate::util::zip::get_file_bytes;
+
+/// Minimum amount of letters needed to tell the direction of a text
+const MIN_DETECTION_LETTERS: usize = 50;
+
+/// Page progression direction, as declared by the spine
+/// `page-progression-direction` attribute
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    Ltr,
+    Rtl,
+}
+
+impl Direction {
+    /// Parses the `page-progression-direction` attribute value. The `default`
+    /// value doesn't declare a direction.
+    pub fn from_attribute(value: &str) -> Option<Self> {
+        match value.trim() {
+            "ltr" => Some(Self::Ltr),
+            "rtl" => Some(Self::Rtl),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ltr => write!(f, "ltr"),
+            Self::Rtl => write!(f, "rtl"),
+        }
+    }
+}
+
+/// The direction declared by the spine contradicts the script of the text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DirectionMismatch {
+    pub declared: Direction,
+    pub detected: Direction,
+}
+
+impl fmt::Display for DirectionMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "The spine declares {} page progression but the text reads {}",
+            self.declared, self.detected
+        )
+    }
+}
+
+/// Whether `c` belongs to a right-to-left script (Hebrew, Arabic, Syriac,
+/// Thaana and their presentation forms)
+fn is_rtl_letter(c: char) -> bool {
+    matches!(
+        c,
+        '\u{0590}'..='\u{08FF}' | '\u{FB1D}'..='\u{FDFF}' | '\u{FE70}'..='\u{FEFF}'
+    ) && c.is_alphabetic()
+}
+
+/// Whether `c` belongs to a left-to-right alphabet (Latin, Greek, Cyrillic).
+/// CJK is left out, as it is written in both directions.
+fn is_ltr_letter(c: char) -> bool {
+    c < '\u{0590}' && c.is_alphabetic()
+}
+
+/// Detects the direction of `text` from the script of its letters. Returns
+/// `None` for mixed texts or texts with too few letters to tell.
+pub fn detect_direction(text: &str) -> Option<Direction> {
+    let (rtl, ltr) = text.chars().fold((0, 0), |(rtl, ltr), c| {
+        (
+            rtl + usize::from(is_rtl_letter(c)),
+            ltr + usize::from(is_ltr_letter(c)),
+        )
+    });
+
+    if rtl + ltr < MIN_DETECTION_LETTERS {
+        return None;
+    }
+
+    if rtl > ltr * 2 {
+        Some(Direction::Rtl)
+    } else if ltr > rtl * 2 {
+        Some(Direction::Ltr)
+    } else {
+        None
+    }
+}
+
+impl Epub {
+    /// Checks the page progression direction declared by the spine against
+    /// the script of the spine documents text, e.g. mostly Latin text marked
+    /// `rtl`. Returns `None` when no direction is declared or it matches.
+    /// Spine documents missing from the archive are skipped.
+    pub async fn direction_mismatch(&self) -> Result<Option<DirectionMismatch>> {
+        let content_opf = self.content_opf().await?;
+
+        let Some(declared) = content_opf.page_progression_direction else {
+            return Ok(None);
+        };
+
+        let mut archive = self.archive.lock().await;
+        let mut text = String::new();
+
+        for spine_item in &content_opf.spine {
+            let Some(item) = content_opf.manifest_item(&spine_item.idref) else {
+                continue;
+            };
+
+            let path = self.resolve_href(&item.href);
+
+            if archive.index_for_name(&path).is_none() {
+                continue;
+            }
+
+            let bytes = get_file_bytes(&mut archive, &path)?;
+            text.push_str(&html_to_text(&String::from_utf8_lossy(&bytes)));
+        }
+
+        Ok(detect_direction(&text)
+            .filter(|detected| *detected != declared)
+            .map(|detected| DirectionMismatch { declared, detected }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use anyhow::Result;
+
+    use super::{Direction, DirectionMismatch, detect_direction};
+    use crate::epub::ValidationIssue;
+    use crate::epub::test_util::{EpubBuilder, content_opf};
+
+    #[tokio::test]
+    async fn detects_direction_from_script() -> Result<()> {
+        let latin = "It was the best of times, it was the worst of times, it was the age of wisdom";
+        let hebrew = "בראשית ברא אלהים את השמים ואת הארץ והארץ היתה תהו ובהו וחשך על פני תהום";
+        let japanese = "吾輩は猫である。名前はまだ無い。どこで生れたかとんと見当がつかぬ。";
+
+        assert_eq!(detect_direction(latin), Some(Direction::Ltr));
+        assert_eq!(detect_direction(hebrew), Some(Direction::Rtl));
+        assert_eq!(detect_direction(japanese), None);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn ignores_the_head_of_content_documents() -> Result<()> {
+        let epub = EpubBuilder::new(
+            &content_opf(
+                "<dc:identifier id=\"BookId\">urn:uuid:1234</dc:identifier>",
+                &[("ch1", "ch1.xhtml", "application/xhtml+xml")],
+            )
+            .replace("<spine>", r#"<spine page-progression-direction="rtl">"#),
+        )
+        .entry(
+            "ch1.xhtml",
+            "<html><head><title>Chapter One of the Book</title>\
+             <style>body { font-family: serif; text-align: justify; margin: auto; }\
+             p.first { text-indent: inherit; font-variant: small-caps; hyphens: auto; }\
+             h1 { page-break-before: always; font-weight: bold; }</style>\
+             </head><body><p>בראשית ברא אלהים את השמים ואת הארץ והארץ היתה תהו ובהו \
+             וחשך על פני תהום</p></body></html>",
+        )
+        .open()?;
+
+        assert_eq!(epub.direction_mismatch().await?, None);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn reports_mismatches_when_validating() -> Result<()> {
+        let epub = EpubBuilder::new(
+            &content_opf(
+                "<dc:identifier id=\"BookId\">urn:uuid:1234</dc:identifier>",
+                &[
+                    ("ch1", "ch1.xhtml", "application/xhtml+xml"),
+                    ("ch2", "missing.xhtml", "application/xhtml+xml"),
+                ],
+            )
+            .replace("<spine>", r#"<spine page-progression-direction="rtl">"#),
+        )
+        .entry(
+            "ch1.xhtml",
+            "<html><body><p>It was the best of times, it was the worst of times, \
+             it was the age of wisdom</p></body></html>",
+        )
+        .open()?;
+        let mismatch = DirectionMismatch {
+            declared: Direction::Rtl,
+            detected: Direction::Ltr,
+        };
+
+        assert_eq!(epub.direction_mismatch().await?, Some(mismatch));
+        assert!(
+            epub.validate()
+                .await?
+                .contains(&ValidationIssue::DirectionMismatch(mismatch))
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,401 @@
+use std::borrow::Cow;
+use std::fs::File;
+use std::io::{Cursor, Read, Seek, Write};
+use std::path::Path;
+
+use anyhow::{Result, bail};
+use tokio::sync::OnceCell;
+use xml::reader::{ParserConfig, XmlEvent};
+use xml::writer::{EmitterConfig, XmlEvent as WriterEvent};
+use zip::write::{ExtendedFileOptions, FileOptions, ZipWriter};
+use zip::{CompressionMethod, ZipArchive};
+
+use crate::epub::container::CONTAINER_XML;
+use crate::epub::content_opf::ContentOpf;
+use crate::epub::mimetype::MIMETYPE;
+use crate::epub::{Epub, ReadSeek};
+use crate::util::fs::replace_with;
+use crate::util::zip::get_file_bytes;
+
+impl Epub {
+    /// Replaces the contents of the `archive_path` entry with `bytes`, copying
+    /// every other entry verbatim. Fails if the archive has no such entry.
+    pub fn replace_file(&mut self, archive_path: &str, bytes: &[u8]) -> Result<()> {
+        if self
+            .archive
+            .get_mut()
+            .index_for_name(archive_path)
+            .is_none()
+        {
+            bail!("The EPUB has no '{}' entry to replace", archive_path);
+        }
+
+        self.put_file(archive_path, bytes)
+    }
+
+    /// Writes `bytes` into the `archive_path` entry, replacing it in place when
+    /// it exists or appending it at the end of the archive otherwise. Every
+    /// other entry is copied verbatim, without recompressing it.
+    ///
+    /// The archive is rewritten next to the EPUB file and moved over it, then
+    /// reopened, so previously parsed metadata is read again on next access.
+    pub fn put_file(&mut self, archive_path: &str, bytes: &[u8]) -> Result<()> {
+        self.rewrite_archive(&[(archive_path, Some(bytes))])
+    }
+
+    /// Removes the `archive_path` entry from the archive, along with the
+    /// manifest item referencing it and its spine `itemref`, so the book
+    /// stays valid. The `mimetype` file, `META-INF/container.xml` and the OPF
+    /// file itself can't be removed.
+    pub fn remove_file(&mut self, archive_path: &str) -> Result<()> {
+        if archive_path == MIMETYPE
+            || archive_path == CONTAINER_XML
+            || archive_path == self.opf_path
+        {
+            bail!("Refusing to remove '{}', the EPUB needs it", archive_path);
+        }
+
+        if self
+            .archive
+            .get_mut()
+            .index_for_name(archive_path)
+            .is_none()
+        {
+            bail!("The EPUB has no '{}' entry to remove", archive_path);
+        }
+
+        let opf = get_file_bytes(self.archive.get_mut(), &self.opf_path)?;
+        let content_opf = ContentOpf::new(opf.clone())?;
+        let manifest_item = content_opf
+            .manifest
+            .iter()
+            .find(|item| self.resolve_href(&item.href) == archive_path);
+
+        let Some(manifest_item) = manifest_item else {
+            return self.rewrite_archive(&[(archive_path, None)]);
+        };
+
+        let opf = without_manifest_item(&opf, &manifest_item.id)?;
+        let opf_path = self.opf_path.clone();
+
+        self.rewrite_archive(&[(archive_path, None), (&opf_path, Some(&opf))])
+    }
+
+    /// Rewrites the archive applying `changes`, pairs of an entry path and
+    /// its new contents, or `None` to drop the entry. Entries missing from the
+    /// archive are appended at the end.
+    fn rewrite_archive(&mut self, changes: &[(&str, Option<&[u8]>)]) -> Result<()> {
+        let Some(path) = self.path.clone() else {
+            bail!("Cannot rewrite an EPUB not opened from a file");
+        };
+        let replaced = replace_with(&path, |temp_path| {
+            write_with_changes(self.archive.get_mut(), temp_path, changes)?;
+            // Make sure the rewritten archive opens before it replaces the book
+            Epub::open(temp_path)?;
+            // Windows can't rename over a file that is still open
+            self.close();
+            Ok(())
+        });
+
+        // Failed before closing the book, so neither the file nor `self` changed
+        if self.path.is_some() {
+            return replaced;
+        }
+
+        // The rewritten book, or the original one when the rename failed. If
+        // it can't be opened `self` stays closed, so reads fail.
+        *self = Epub::open(&path)
+            .map_err(|err| err.context(format!("Failed to reopen '{}'", path.display())))?;
+
+        replaced
+    }
+
+    /// Closes the book file, dropping the archive and everything parsed from
+    /// it, so reads fail until the book is opened again. The `path` is
+    /// forgotten too, so the book can't be rewritten either.
+    fn close(&mut self) {
+        let empty = ZipWriter::new(Cursor::new(Vec::new()))
+            .finish()
+            .map(Cursor::into_inner)
+            .unwrap_or_default();
+
+        if let Ok(archive) = ZipArchive::new(Box::new(Cursor::new(empty)) as Box<dyn ReadSeek>) {
+            *self.archive.get_mut() = archive;
+        }
+
+        self.path = None;
+        self.toc = OnceCell::new();
+        self.content_opf = OnceCell::new();
+    }
+}
+
+/// Copies `archive` into a new archive at `path`, applying `changes` as
+/// described in [`Epub::rewrite_archive`].
+fn write_with_changes<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    path: &Path,
+    changes: &[(&str, Option<&[u8]>)],
+) -> Result<()> {
+    let mut zip_writer = ZipWriter::new(File::create(path)?);
+    let mut written = Vec::new();
+
+    for index in 0..archive.len() {
+        let file = archive.by_index_raw(index)?;
+        let Some((name, contents)) = changes.iter().find(|(name, _)| *name == file.name()) else {
+            zip_writer.raw_copy_file(file)?;
+            continue;
+        };
+
+        written.push(*name);
+
+        let Some(contents) = contents else {
+            continue;
+        };
+
+        let mut options: FileOptions<'_, ExtendedFileOptions> =
+            FileOptions::default().compression_method(file.compression());
+
+        if let Some(last_modified) = file.last_modified() {
+            options = options.last_modified_time(last_modified);
+        }
+
+        zip_writer.start_file(*name, options)?;
+        zip_writer.write_all(contents)?;
+    }
+
+    for (name, contents) in changes {
+        if let Some(contents) = contents
+            && !written.contains(name)
+        {
+            let options: FileOptions<'_, ExtendedFileOptions> =
+                FileOptions::default().compression_method(CompressionMethod::Deflated);
+
+            zip_writer.start_file(*name, options)?;
+            zip_writer.write_all(contents)?;
+        }
+    }
+
+    zip_writer.finish()?;
+
+    Ok(())
+}
+
+/// Rewrites the OPF file without the manifest `item` with the given `id`, the
+/// spine `itemref`s pointing to it and the spine `toc` attribute when it
+/// references it. Everything else is copied as is.
+fn without_manifest_item(opf: &[u8], id: &str) -> Result<Vec<u8>> {
+    let xml_reader = ParserConfig::new()
+        .ignore_comments(false)
+        .create_reader(opf);
+    let mut output = Vec::new();
+    let mut xml_writer = EmitterConfig::new()
+        .perform_indent(false)
+        .autopad_comments(false)
+        .pad_self_closing(false)
+        .create_writer(&mut output);
+    // Depth within a removed element, 0 while copying
+    let mut skipping = 0;
+
+    for event in xml_reader {
+        let event = event?;
+
+        if skipping > 0 {
+            match event {
+                XmlEvent::StartElement { .. } => skipping += 1,
+                XmlEvent::EndElement { .. } => skipping -= 1,
+                _ => {}
+            }
+
+            continue;
+        }
+
+        if let XmlEvent::StartElement {
+            name,
+            attributes,
+            namespace,
+        } = &event
+        {
+            let attribute = |key: &str| {
+                attributes
+                    .iter()
+                    .find(|attr| attr.name.local_name == key)
+                    .map(|attr| attr.value.as_str())
+            };
+
+            match name.local_name.as_str() {
+                "item" if attribute("id") == Some(id) => {
+                    skipping = 1;
+                    continue;
+                }
+                "itemref" if attribute("idref") == Some(id) => {
+                    skipping = 1;
+                    continue;
+                }
+                "spine" if attribute("toc") == Some(id) => {
+                    xml_writer.write(WriterEvent::StartElement {
+                        name: name.borrow(),
+                        attributes: attributes
+                            .iter()
+                            .filter(|attr| attr.name.local_name != "toc")
+                            .map(|attr| attr.borrow())
+                            .collect(),
+                        namespace: Cow::Borrowed(namespace),
+                    })?;
+                    continue;
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(event) = event.as_writer_event() {
+            xml_writer.write(event)?;
+        }
+    }
+
+    Ok(output)
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs::remove_file;
+    use std::path::Path;
+
+    use anyhow::Result;
+
+    use super::without_manifest_item;
+    use crate::epub::Epub;
+    use crate::epub::test_util::EpubBuilder;
+
+    fn content_opf(title: &str) -> String {
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="2.0" unique-identifier="BookId">
+    <metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+        <dc:title>{}</dc:title>
+        <dc:identifier id="BookId">urn:uuid:1234</dc:identifier>
+    </metadata>
+    <manifest>
+        <item id="ncx" href="toc.ncx" media-type="application/x-dtbncx+xml"/>
+        <item id="ch1" href="ch1.xhtml" media-type="application/xhtml+xml"/>
+    </manifest>
+    <spine toc="ncx">
+        <!-- Reading order -->
+        <itemref idref="ch1"/>
+    </spine>
+</package>
+"#,
+            title
+        )
+    }
+
+    fn write_epub(path: &Path) -> Result<()> {
+        EpubBuilder::new(&content_opf("Before"))
+            .entry("cover.jpg", "jpeg")
+            .entry("ch1.xhtml", "<html/>")
+            .write(path)
+    }
+
+    #[tokio::test]
+    async fn replaces_a_single_entry() -> Result<()> {
+        let path = std::env::temp_dir().join(format!("bookworm-edit-{}.epub", std::process::id()));
+        write_epub(&path)?;
+
+        let mut epub = Epub::open(&path)?;
+        assert_eq!(epub.content_opf().await?.metadata.title, "Before");
+
+        epub.replace_file("content.opf", content_opf("After").as_bytes())?;
+        assert_eq!(epub.content_opf().await?.metadata.title, "After");
+        assert!(epub.replace_file("missing.jpg", b"jpeg").is_err());
+
+        epub.put_file("missing.jpg", b"jpeg")?;
+        assert_eq!(
+            epub.list_entries().await?,
+            [
+                "mimetype",
+                "META-INF/container.xml",
+                "content.opf",
+                "cover.jpg",
+                "ch1.xhtml",
+                "missing.jpg"
+            ]
+        );
+
+        remove_file(path)?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn removes_an_entry_and_its_manifest_item() -> Result<()> {
+        let path =
+            std::env::temp_dir().join(format!("bookworm-remove-{}.epub", std::process::id()));
+        write_epub(&path)?;
+
+        let mut epub = Epub::open(&path)?;
+        assert!(epub.remove_file("mimetype").is_err());
+        assert!(epub.remove_file("content.opf").is_err());
+
+        epub.remove_file("ch1.xhtml")?;
+        epub.remove_file("cover.jpg")?;
+
+        let content_opf = epub.content_opf().await?;
+        assert_eq!(
+            epub.list_entries().await?,
+            ["mimetype", "META-INF/container.xml", "content.opf"]
+        );
+        assert_eq!(content_opf.metadata.title, "Before");
+        assert_eq!(content_opf.manifest.len(), 1);
+        assert!(content_opf.spine.is_empty());
+
+        remove_file(path)?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn keeps_the_book_when_the_rewrite_does_not_open() -> Result<()> {
+        let path =
+            std::env::temp_dir().join(format!("bookworm-reopen-{}.epub", std::process::id()));
+        write_epub(&path)?;
+
+        let mut epub = Epub::open(&path)?;
+        assert!(
+            epub.rewrite_archive(&[("META-INF/container.xml", None)])
+                .is_err()
+        );
+        assert!(
+            !path
+                .with_file_name(format!(
+                    ".bookworm-reopen-{0}.epub.{0}.tmp",
+                    std::process::id()
+                ))
+                .exists()
+        );
+        assert_eq!(epub.content_opf().await?.metadata.title, "Before");
+        assert_eq!(
+            Epub::open(&path)?.content_opf().await?.metadata.title,
+            "Before"
+        );
+
+        epub.close();
+        assert!(epub.content_opf().await.is_err());
+        assert!(epub.put_file("cover.jpg", b"png").is_err());
+
+        remove_file(path)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn drops_the_spine_toc_attribute() -> Result<()> {
+        let opf = without_manifest_item(content_opf("Title").as_bytes(), "ncx")?;
+        let opf = String::from_utf8(opf)?;
+
+        assert!(opf.contains("<spine>"));
+        assert!(opf.contains("<!-- Reading order -->"));
+        assert!(opf.contains(r#"<itemref idref="ch1"/>"#));
+        assert!(!opf.contains("toc.ncx"));
+
+        Ok(())
+    }
+}
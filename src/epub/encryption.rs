@@ -0,0 +1,412 @@
+use std::fs::{remove_file, write};
+use std::io::Cursor;
+use std::path::Path;
+
+use anyhow::{Result, bail};
+use sha1::{Digest, Sha1};
+use xml::{EventReader, reader::XmlEvent};
+
+use crate::epub::Epub;
+use crate::util::href::percent_decode;
+use crate::util::zip::{get_file_bytes, safe_join};
+
+pub const ENCRYPTION_XML: &str = "META-INF/encryption.xml";
+
+/// Rights management file written by DRM schemes such as Adobe ADEPT
+pub const RIGHTS_XML: &str = "META-INF/rights.xml";
+
+/// Font obfuscation algorithm defined by the IDPF in the OCF specification
+pub const IDPF_FONT_ALGORITHM: &str = "http://www.idpf.org/2008/embedding";
+
+/// Font obfuscation algorithm used by Adobe Digital Editions
+pub const ADOBE_FONT_ALGORITHM: &str = "http://ns.adobe.com/pdf/enc#RC";
+
+/// Algorithms used to obfuscate embedded fonts
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FontObfuscation {
+    Idpf,
+    Adobe,
+}
+
+impl FontObfuscation {
+    /// Reverts the obfuscation applied to `data` using the key derived from
+    /// the book's unique identifier.
+    ///
+    /// - IDPF XORs the first 1040 bytes with the SHA-1 digest of the
+    ///   identifier, stripped of whitespace.
+    /// - Adobe XORs the first 1024 bytes with the 16 bytes of the identifier
+    ///   UUID.
+    ///
+    /// XOR being its own inverse, this also obfuscates plain data.
+    pub fn deobfuscate(&self, data: &mut [u8], unique_identifier: &str) -> Result<()> {
+        let (key, length) = match self {
+            Self::Idpf => {
+                let identifier = unique_identifier
+                    .chars()
+                    .filter(|c| !matches!(c, ' ' | '\t' | '\r' | '\n'))
+                    .collect::<String>();
+
+                (Sha1::digest(identifier.as_bytes()).to_vec(), 1040)
+            }
+            Self::Adobe => (adobe_key(unique_identifier)?, 1024),
+        };
+
+        for (index, byte) in data.iter_mut().take(length).enumerate() {
+            *byte ^= key[index % key.len()];
+        }
+
+        Ok(())
+    }
+}
+
+fn adobe_key(unique_identifier: &str) -> Result<Vec<u8>> {
+    let hex = unique_identifier
+        .trim()
+        .trim_start_matches("urn:uuid:")
+        .replace('-', "");
+
+    if hex.len() != 32 {
+        bail!(
+            "Failed to derive Adobe font key. Identifier '{}' is not a UUID",
+            unique_identifier
+        );
+    }
+
+    (0..hex.len())
+        .step_by(2)
+        .map(|index| {
+            u8::from_str_radix(&hex[index..index + 2], 16).map_err(|_| {
+                anyhow::anyhow!(
+                    "Failed to derive Adobe font key. Identifier '{}' is not a UUID",
+                    unique_identifier
+                )
+            })
+        })
+        .collect()
+}
+
+/// The `EncryptedData` element in the `META-INF/encryption.xml` file
+#[derive(Debug, Clone)]
+pub struct EncryptedData {
+    /// The `Algorithm` attribute of the `EncryptionMethod` element
+    pub algorithm: String,
+    /// The `URI` attribute of the `CipherReference` element, relative to the
+    /// root of the archive
+    pub uri: String,
+}
+
+impl EncryptedData {
+    /// Returns the path of the resource in the archive, the `URI` with its
+    /// `%XX` escapes decoded
+    pub fn path(&self) -> String {
+        percent_decode(self.uri.trim_start_matches('/'))
+    }
+
+    /// Returns the font obfuscation algorithm, if the resource is obfuscated
+    /// rather than encrypted.
+    pub fn font_obfuscation(&self) -> Option<FontObfuscation> {
+        match self.algorithm.as_str() {
+            IDPF_FONT_ALGORITHM => Some(FontObfuscation::Idpf),
+            ADOBE_FONT_ALGORITHM => Some(FontObfuscation::Adobe),
+            _ => None,
+        }
+    }
+}
+
+/// Representation of the `META-INF/encryption.xml` file in an EPUB archive.
+#[derive(Debug, Clone)]
+pub struct Encryption {
+    pub encrypted_data: Vec<EncryptedData>,
+}
+
+impl Encryption {
+    /// Parses the `META-INF/encryption.xml` file and extracts the encrypted
+    /// resources.
+    pub fn new(bytes: Vec<u8>) -> Result<Self> {
+        let cursor = Cursor::new(bytes);
+        let xml_reader = EventReader::new(cursor);
+        let mut encrypted_data = Vec::new();
+        let mut algorithm = String::new();
+        let mut uri = String::new();
+
+        for event in xml_reader {
+            match event? {
+                XmlEvent::StartElement {
+                    name, attributes, ..
+                } => {
+                    let find_attr = |local_name: &str| {
+                        attributes
+                            .iter()
+                            .find(|attr| attr.name.local_name == local_name)
+                            .map(|attr| attr.value.clone())
+                    };
+
+                    match name.local_name.as_str() {
+                        "EncryptionMethod" => {
+                            algorithm = find_attr("Algorithm").unwrap_or_default();
+                        }
+                        "CipherReference" => uri = find_attr("URI").unwrap_or_default(),
+                        _ => {}
+                    }
+                }
+                XmlEvent::EndElement { name } if name.local_name == "EncryptedData" => {
+                    encrypted_data.push(EncryptedData {
+                        algorithm: std::mem::take(&mut algorithm),
+                        uri: std::mem::take(&mut uri),
+                    });
+                }
+                _ => {}
+            }
+        }
+
+        Ok(Self { encrypted_data })
+    }
+
+    /// Finds the encryption entry for the resource at `path` in the archive.
+    pub fn find(&self, path: &str) -> Option<&EncryptedData> {
+        self.encrypted_data.iter().find(|data| data.path() == path)
+    }
+}
+
+impl Epub {
+    /// Parses `META-INF/encryption.xml` when the archive includes it.
+    pub async fn encryption(&self) -> Result<Option<Encryption>> {
+        let mut archive = self.archive.lock().await;
+
+        if archive.index_for_name(ENCRYPTION_XML).is_none() {
+            return Ok(None);
+        }
+
+        let bytes = get_file_bytes(&mut archive, ENCRYPTION_XML)?;
+
+        Encryption::new(bytes).map(Some)
+    }
+
+    /// Reads the resource at the manifest `href`, relative to the OPF file,
+    /// reverting the font obfuscation declared for it in
+    /// `META-INF/encryption.xml`. Resources not listed there are returned
+    /// as stored, while encrypted ones fail as only obfuscation can be
+    /// reverted.
+    pub async fn read_resource_deobfuscated(&self, href: &str) -> Result<Vec<u8>> {
+        let path = self.resolve_href(href);
+        let encryption = self.encryption().await?;
+        let unique_identifier = self.content_opf().await?.primary_identifier();
+        let mut bytes = get_file_bytes(&mut *self.archive.lock().await, &path)?;
+
+        if let Some(data) = encryption
+            .as_ref()
+            .and_then(|encryption| encryption.find(&path))
+        {
+            let Some(obfuscation) = data.font_obfuscation() else {
+                bail!(
+                    "Failed to read '{}'. The resource is encrypted with '{}'",
+                    path,
+                    data.algorithm
+                );
+            };
+
+            obfuscation.deobfuscate(&mut bytes, unique_identifier)?;
+        }
+
+        Ok(bytes)
+    }
+
+    /// Checks whether the book is DRM-protected: it ships a
+    /// `META-INF/rights.xml` file or `META-INF/encryption.xml` declares
+    /// resources encrypted with anything other than font obfuscation.
+    ///
+    /// Obfuscated fonts alone don't count, as plenty of DRM-free books use
+    /// them.
+    pub async fn is_drm_protected(&self) -> Result<bool> {
+        if self
+            .archive
+            .lock()
+            .await
+            .index_for_name(RIGHTS_XML)
+            .is_some()
+        {
+            return Ok(true);
+        }
+
+        Ok(self.encryption().await?.is_some_and(|encryption| {
+            encryption
+                .encrypted_data
+                .iter()
+                .any(|data| data.font_obfuscation().is_none())
+        }))
+    }
+
+    /// Fails with a clear message when the book is DRM-protected, so its
+    /// encrypted content documents aren't read as text.
+    pub(crate) async fn ensure_not_drm_protected(&self) -> Result<()> {
+        if self.is_drm_protected().await? {
+            bail!("The EPUB is DRM-protected, its content is encrypted and can't be read");
+        }
+
+        Ok(())
+    }
+
+    /// Rewrites the obfuscated fonts of an EPUB previously unpackaged into
+    /// `outdir` with their de-obfuscated contents.
+    ///
+    /// When every entry of `META-INF/encryption.xml` was a de-obfuscated font,
+    /// the file is removed so the resources aren't treated as obfuscated on
+    /// repackaging. Returns the archive paths of the de-obfuscated fonts.
+    pub async fn deobfuscate_fonts<P: AsRef<Path>>(&self, outdir: P) -> Result<Vec<String>> {
+        let Some(encryption) = self.encryption().await? else {
+            return Ok(Vec::new());
+        };
+
+        let outdir = outdir.as_ref();
+        let unique_identifier = self.content_opf().await?.primary_identifier();
+        let mut archive = self.archive.lock().await;
+        let mut deobfuscated = Vec::new();
+
+        for data in &encryption.encrypted_data {
+            let Some(obfuscation) = data.font_obfuscation() else {
+                continue;
+            };
+
+            let path = data.path();
+            let mut bytes = get_file_bytes(&mut archive, &path)?;
+
+            obfuscation.deobfuscate(&mut bytes, unique_identifier)?;
+            write(safe_join(outdir, &path)?, bytes)?;
+            deobfuscated.push(path);
+        }
+
+        if deobfuscated.len() == encryption.encrypted_data.len() {
+            remove_file(outdir.join(ENCRYPTION_XML))?;
+        }
+
+        Ok(deobfuscated)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use anyhow::Result;
+
+    use super::{ADOBE_FONT_ALGORITHM, FontObfuscation, IDPF_FONT_ALGORITHM};
+    use crate::epub::Epub;
+    use crate::epub::test_util::{EpubBuilder, content_opf};
+
+    /// Builds an EPUB whose `encryption.xml` lists `(algorithm, uri)` pairs
+    fn encrypted_epub(encrypted: &[(&str, &str)], rights: bool) -> Result<Epub> {
+        let encryption_xml = encrypted
+            .iter()
+            .map(|(algorithm, uri)| {
+                format!(
+                    "<enc:EncryptedData><enc:EncryptionMethod Algorithm=\"{}\"/>\
+                     <enc:CipherData><enc:CipherReference URI=\"{}\"/></enc:CipherData>\
+                     </enc:EncryptedData>",
+                    algorithm, uri
+                )
+            })
+            .collect::<String>();
+        let mut builder = EpubBuilder::new(&content_opf(
+            "<dc:identifier id=\"BookId\">urn:uuid:12345678-1234-1234-1234-123456789abc</dc:identifier>",
+            &[
+                ("ch1", "ch1.xhtml", "application/xhtml+xml"),
+                ("font", "font.otf", "font/otf"),
+            ],
+        ))
+        .entry(
+            "META-INF/encryption.xml",
+            format!(
+                "<encryption xmlns=\"urn:oasis:names:tc:opendocument:xmlns:container\" \
+                 xmlns:enc=\"http://www.w3.org/2001/04/xmlenc#\">{}</encryption>",
+                encryption_xml
+            ),
+        )
+        .entry("ch1.xhtml", "<html><body><p>Text</p></body></html>")
+        .entry("font.otf", "font");
+
+        if rights {
+            builder = builder.entry("META-INF/rights.xml", "<rights/>");
+        }
+
+        builder.open()
+    }
+
+    #[tokio::test]
+    async fn detects_drm_but_not_font_obfuscation() -> Result<()> {
+        for algorithm in [IDPF_FONT_ALGORITHM, ADOBE_FONT_ALGORITHM] {
+            let epub = encrypted_epub(&[(algorithm, "font.otf")], false)?;
+
+            assert!(!epub.is_drm_protected().await?);
+            assert_eq!(epub.text().await?, "Text");
+        }
+
+        let epub = encrypted_epub(
+            &[
+                (IDPF_FONT_ALGORITHM, "font.otf"),
+                ("http://www.w3.org/2001/04/xmlenc#aes128-cbc", "ch1.xhtml"),
+            ],
+            false,
+        )?;
+
+        assert!(epub.is_drm_protected().await?);
+        assert!(epub.text().await.is_err());
+        assert!(encrypted_epub(&[], true)?.is_drm_protected().await?);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn reads_resources_reverting_font_obfuscation() -> Result<()> {
+        let mut font = b"font".to_vec();
+        FontObfuscation::Idpf
+            .deobfuscate(&mut font, "urn:uuid:12345678-1234-1234-1234-123456789abc")?;
+
+        let epub = encrypted_epub(&[(IDPF_FONT_ALGORITHM, "font.otf")], false)?;
+
+        assert_eq!(epub.read_resource_deobfuscated("font.otf").await?, font);
+        assert_eq!(
+            epub.read_resource_deobfuscated("ch1.xhtml").await?,
+            b"<html><body><p>Text</p></body></html>"
+        );
+
+        let epub = encrypted_epub(
+            &[("http://www.w3.org/2001/04/xmlenc#aes128-cbc", "font.otf")],
+            false,
+        )?;
+
+        assert!(epub.read_resource_deobfuscated("font.otf").await.is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn deobfuscates_idpf_fonts_with_identifier_digest() -> Result<()> {
+        // SHA-1 of `9780306406157`
+        const KEY: &str = "10d9e6d5f1c5fe95fdb8bc589c5644abfe8d5209";
+
+        let mut data = vec![0; 1100];
+        FontObfuscation::Idpf.deobfuscate(&mut data, " 9780306406157\n")?;
+
+        let key = (0..KEY.len())
+            .step_by(2)
+            .map(|index| u8::from_str_radix(&KEY[index..index + 2], 16))
+            .collect::<Result<Vec<u8>, _>>()?;
+
+        assert_eq!(&data[..20], key.as_slice());
+        assert_eq!(&data[1020..1040], key.as_slice());
+        assert!(data[1040..].iter().all(|byte| *byte == 0));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn deobfuscates_adobe_fonts_with_identifier_uuid() -> Result<()> {
+        let mut data = vec![0; 1100];
+        FontObfuscation::Adobe
+            .deobfuscate(&mut data, "urn:uuid:12345678-1234-1234-1234-123456789abc")?;
+
+        assert_eq!(&data[..4], &[0x12, 0x34, 0x56, 0x78]);
+        assert_eq!(&data[1008..1024], &data[..16]);
+        assert!(data[1024..].iter().all(|byte| *byte == 0));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,105 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use serde_json::Value;
+use ureq::Agent;
+
+const OPEN_LIBRARY_BOOKS_URL: &str = "https://openlibrary.org/api/books";
+const LOOKUP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Metadata found for an ISBN in the OpenLibrary catalog
+#[derive(Debug, Default)]
+pub struct IsbnRecord {
+    pub title: Option<String>,
+    pub authors: Vec<String>,
+    pub publisher: Option<String>,
+    pub cover_url: Option<String>,
+}
+
+impl IsbnRecord {
+    fn from_json(value: &Value) -> Self {
+        let names = |key: &str| {
+            value[key]
+                .as_array()
+                .map(|entries| {
+                    entries
+                        .iter()
+                        .filter_map(|entry| entry["name"].as_str().map(String::from))
+                        .collect::<Vec<String>>()
+                })
+                .unwrap_or_default()
+        };
+
+        IsbnRecord {
+            title: value["title"].as_str().map(String::from),
+            authors: names("authors"),
+            publisher: names("publishers").into_iter().next(),
+            cover_url: ["large", "medium", "small"]
+                .iter()
+                .find_map(|size| value["cover"][size].as_str())
+                .map(String::from),
+        }
+    }
+}
+
+/// Looks up the ISBN in the OpenLibrary catalog. Returns `None` when the ISBN
+/// is unknown to OpenLibrary.
+///
+/// This performs a blocking network request and is only available with the
+/// `online` feature.
+pub fn lookup_isbn(isbn: &str) -> Result<Option<IsbnRecord>> {
+    let agent: Agent = Agent::config_builder()
+        .timeout_global(Some(LOOKUP_TIMEOUT))
+        .build()
+        .into();
+    let bibkey = format!("ISBN:{}", isbn);
+    let body = agent
+        .get(OPEN_LIBRARY_BOOKS_URL)
+        .query("bibkeys", &bibkey)
+        .query("format", "json")
+        .query("jscmd", "data")
+        .call()?
+        .body_mut()
+        .read_to_string()?;
+    let response: Value = serde_json::from_str(&body)?;
+
+    Ok(response.get(&bibkey).map(IsbnRecord::from_json))
+}
+
+#[cfg(test)]
+mod test {
+    use anyhow::Result;
+    use serde_json::json;
+
+    use super::IsbnRecord;
+
+    #[tokio::test]
+    async fn reads_open_library_book_data() -> Result<()> {
+        let record = IsbnRecord::from_json(&json!({
+            "title": "Dune",
+            "authors": [{ "name": "Frank Herbert", "url": "https://openlibrary.org/authors/OL79034A" }],
+            "publishers": [{ "name": "Ace Books" }, { "name": "Chilton Books" }],
+            "cover": {
+                "small": "https://covers.openlibrary.org/b/id/1-S.jpg",
+                "medium": "https://covers.openlibrary.org/b/id/1-M.jpg"
+            }
+        }));
+
+        assert_eq!(record.title.as_deref(), Some("Dune"));
+        assert_eq!(record.authors, ["Frank Herbert"]);
+        assert_eq!(record.publisher.as_deref(), Some("Ace Books"));
+        assert_eq!(
+            record.cover_url.as_deref(),
+            Some("https://covers.openlibrary.org/b/id/1-M.jpg")
+        );
+
+        let record = IsbnRecord::from_json(&json!({ "authors": [{ "url": "" }] }));
+
+        assert!(record.title.is_none());
+        assert!(record.authors.is_empty());
+        assert!(record.publisher.is_none());
+        assert!(record.cover_url.is_none());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,65 @@
+use anyhow::Result;
+
+use crate::epub::Epub;
+use crate::epub::encryption::FontObfuscation;
+
+/// An embedded font declared in the OPF manifest
+#[derive(Debug, Clone)]
+pub struct FontEntry {
+    pub id: String,
+    pub href: String,
+    pub media_type: String,
+    /// Uncompressed size in bytes of the font file in the archive
+    pub size: u64,
+    /// Obfuscation algorithm declared in `META-INF/encryption.xml`
+    pub obfuscation: Option<FontObfuscation>,
+}
+
+fn is_font_media_type(media_type: &str) -> bool {
+    media_type.starts_with("font/")
+        || matches!(
+            media_type,
+            "application/vnd.ms-opentype"
+                | "application/font-sfnt"
+                | "application/font-woff"
+                | "application/font-woff2"
+                | "application/x-font-ttf"
+                | "application/x-font-otf"
+                | "application/x-font-truetype"
+                | "application/x-font-opentype"
+        )
+}
+
+impl Epub {
+    /// Lists the fonts embedded in the EPUB, along with their size and
+    /// whether they are obfuscated.
+    pub async fn fonts(&self) -> Result<Vec<FontEntry>> {
+        let encryption = self.encryption().await?;
+        let content_opf = self.content_opf().await?;
+        let mut archive = self.archive.lock().await;
+        let mut fonts = Vec::new();
+
+        for item in &content_opf.manifest {
+            if !is_font_media_type(&item.media_type) {
+                continue;
+            }
+
+            let path = self.resolve_href(&item.href);
+            let size = archive.by_name(&path)?.size();
+            let obfuscation = encryption
+                .as_ref()
+                .and_then(|encryption| encryption.find(&path))
+                .and_then(|data| data.font_obfuscation());
+
+            fonts.push(FontEntry {
+                id: item.id.clone(),
+                href: item.href.clone(),
+                media_type: item.media_type.clone(),
+                size,
+                obfuscation,
+            });
+        }
+
+        Ok(fonts)
+    }
+}
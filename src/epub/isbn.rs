@@ -0,0 +1,53 @@
+/// Normalizes an identifier into a bare ISBN-10 or ISBN-13, dropping an
+/// `urn:isbn:` prefix and any hyphens or spaces. Returns `None` when the
+/// identifier is not shaped like an ISBN.
+pub fn normalize_isbn(identifier: &str) -> Option<String> {
+    let identifier = identifier.trim();
+    let identifier = identifier
+        .get(..9)
+        .filter(|prefix| prefix.eq_ignore_ascii_case("urn:isbn:"))
+        .map_or(identifier, |_| &identifier[9..]);
+    let isbn = identifier
+        .chars()
+        .filter(|c| *c != '-' && !c.is_whitespace())
+        .map(|c| c.to_ascii_uppercase())
+        .collect::<String>();
+    let is_isbn = match isbn.len() {
+        10 => {
+            isbn[..9].chars().all(|c| c.is_ascii_digit())
+                && isbn[9..].chars().all(|c| c.is_ascii_digit() || c == 'X')
+        }
+        13 => isbn.chars().all(|c| c.is_ascii_digit()),
+        _ => false,
+    };
+
+    is_isbn.then_some(isbn)
+}
+
+/// Checks that the identifier is an ISBN-10 or ISBN-13 with a valid check
+/// digit. Accepts the same formats as [`normalize_isbn`].
+pub fn is_valid_isbn(identifier: &str) -> bool {
+    let Some(isbn) = normalize_isbn(identifier) else {
+        return false;
+    };
+    let digits = isbn.chars().map(|c| match c {
+        'X' => 10,
+        c => c.to_digit(10).unwrap_or_default(),
+    });
+
+    if isbn.len() == 10 {
+        digits
+            .zip((1..=10).rev())
+            .map(|(digit, weight)| digit * weight)
+            .sum::<u32>()
+            % 11
+            == 0
+    } else {
+        digits
+            .zip([1, 3].into_iter().cycle())
+            .map(|(digit, weight)| digit * weight)
+            .sum::<u32>()
+            % 10
+            == 0
+    }
+}
@@ -0,0 +1,240 @@
+use std::fs::{read_to_string, remove_dir_all, write};
+use std::path::Path;
+
+use anyhow::Result;
+
+use crate::epub::text::BLOCK_ELEMENTS;
+use crate::epub::{Epub, EpubWriter, extract_archive};
+use crate::util::zip::safe_join;
+
+/// Class of the spans Kobo readers use to track the reading progress
+pub const KOBO_SPAN_CLASS: &str = "koboSpan";
+
+/// Elements whose text is never wrapped in Kobo spans
+const UNWRAPPED_ELEMENTS: &[&str] = &["head", "math", "script", "style", "svg"];
+
+impl Epub {
+    /// Converts the book into a Kobo-enhanced EPUB (KEPUB) written to
+    /// `output`, conventionally named `*.kepub.epub`.
+    ///
+    /// The book is extracted into a temporary directory, the text of each
+    /// spine document is wrapped in `koboSpan` spans with
+    /// [`inject_kobo_spans`], and the directory is packaged again with
+    /// [`EpubWriter::repack`]. Every other file, including stylesheets and
+    /// images, is copied untouched.
+    pub async fn to_kepub<P: AsRef<Path>>(&self, output: P) -> Result<()> {
+        let content_opf = self.content_opf().await?;
+        let workdir = std::env::temp_dir().join(format!(
+            "bookworm-kepub-{}-{}",
+            std::process::id(),
+            content_opf
+                .primary_identifier()
+                .replace([':', '/', '\\'], "-")
+        ));
+        let result = async {
+            extract_archive(&mut *self.archive.lock().await, &workdir)?;
+
+            for spine_item in &content_opf.spine {
+                let Some(item) = content_opf.manifest_item(&spine_item.idref) else {
+                    continue;
+                };
+                let path = safe_join(&workdir, &self.resolve_href(&item.href))?;
+
+                if !path.is_file() {
+                    continue;
+                }
+
+                write(&path, inject_kobo_spans(&read_to_string(&path)?))?;
+            }
+
+            EpubWriter::repack(&workdir, output).await
+        }
+        .await;
+
+        let _ = remove_dir_all(&workdir);
+
+        result
+    }
+}
+
+/// Wraps each sentence of the `body` text of an (X)HTML document in a
+/// `<span class="koboSpan" id="kobo.P.S">`, where `P` counts the paragraphs
+/// (block elements) and `S` the sentences within them, both from 1.
+///
+/// Only text nodes are touched, so the existing markup is kept as is.
+/// Documents already holding Kobo spans are returned unchanged.
+pub fn inject_kobo_spans(xhtml: &str) -> String {
+    if xhtml.contains(KOBO_SPAN_CLASS) {
+        return xhtml.to_string();
+    }
+
+    let mut output = String::with_capacity(xhtml.len() * 2);
+    let mut in_body = false;
+    let mut skip_depth = 0_usize;
+    let mut paragraph = 0_usize;
+    let mut sentence = 0_usize;
+    let mut new_paragraph = true;
+    let mut rest = xhtml;
+
+    while !rest.is_empty() {
+        let tag_start = rest.find('<').unwrap_or(rest.len());
+        let text = &rest[..tag_start];
+
+        if in_body && skip_depth == 0 && !text.trim().is_empty() {
+            if new_paragraph {
+                paragraph += 1;
+                sentence = 0;
+                new_paragraph = false;
+            }
+
+            for segment in split_sentences(text) {
+                if segment.trim().is_empty() {
+                    output.push_str(segment);
+                    continue;
+                }
+
+                sentence += 1;
+                output.push_str(&format!(
+                    r#"<span class="{}" id="kobo.{}.{}">{}</span>"#,
+                    KOBO_SPAN_CLASS, paragraph, sentence, segment
+                ));
+            }
+        } else {
+            output.push_str(text);
+        }
+
+        rest = &rest[tag_start..];
+
+        if rest.is_empty() {
+            break;
+        }
+
+        // Comments and CDATA sections may hold `>` before their end
+        let tag_end = if rest.starts_with("<!--") {
+            rest.find("-->").map(|end| end + 3)
+        } else if rest.starts_with("<![CDATA[") {
+            rest.find("]]>").map(|end| end + 3)
+        } else {
+            rest.find('>').map(|end| end + 1)
+        };
+        let tag_end = tag_end.unwrap_or(rest.len());
+        let tag = &rest[..tag_end];
+
+        output.push_str(tag);
+        rest = &rest[tag_end..];
+
+        if tag.starts_with("<!") || tag.starts_with("<?") {
+            continue;
+        }
+
+        let closing = tag.starts_with("</");
+        let self_closing = tag.ends_with("/>");
+        let name = tag
+            .trim_start_matches(['<', '/'])
+            .split(|c: char| c.is_whitespace() || c == '>' || c == '/')
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+
+        if name == "body" {
+            in_body = !closing;
+        } else if UNWRAPPED_ELEMENTS.contains(&name.as_str()) && !self_closing {
+            if closing {
+                skip_depth = skip_depth.saturating_sub(1);
+            } else {
+                skip_depth += 1;
+            }
+        } else if BLOCK_ELEMENTS.contains(&name.as_str()) || name == "br" {
+            new_paragraph = true;
+        }
+    }
+
+    output
+}
+
+/// Splits `text` after each run of sentence ending punctuation followed by
+/// whitespace, keeping the whitespace with the preceding sentence. Leading
+/// whitespace is returned as a segment of its own.
+fn split_sentences(text: &str) -> Vec<&str> {
+    let mut segments = Vec::new();
+    let content_start = text.len() - text.trim_start().len();
+
+    if content_start > 0 {
+        segments.push(&text[..content_start]);
+    }
+
+    let mut start = content_start;
+    let mut chars = text[content_start..].char_indices().peekable();
+    let mut after_punctuation = false;
+
+    while let Some((offset, c)) = chars.next() {
+        if matches!(c, '.' | '!' | '?' | '…' | '。' | '！' | '？') {
+            after_punctuation = true;
+            continue;
+        }
+
+        if after_punctuation && c.is_whitespace() {
+            // Keep every whitespace character with the sentence
+            let mut end = content_start + offset + c.len_utf8();
+
+            while let Some((offset, c)) = chars.peek().copied() {
+                if !c.is_whitespace() {
+                    break;
+                }
+
+                end = content_start + offset + c.len_utf8();
+                chars.next();
+            }
+
+            segments.push(&text[start..end]);
+            start = end;
+        }
+
+        after_punctuation = false;
+    }
+
+    if start < text.len() {
+        segments.push(&text[start..]);
+    }
+
+    segments
+}
+
+#[cfg(test)]
+mod test {
+    use super::inject_kobo_spans;
+
+    #[test]
+    fn wraps_body_sentences_in_kobo_spans() {
+        let xhtml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<html xmlns="http://www.w3.org/1999/xhtml">
+<head><title>Chapter 1</title><link rel="stylesheet" href="style.css"/></head>
+<body>
+<h1>Chapter 1</h1>
+<p>It was cold. The clocks <em>struck</em> thirteen!</p>
+<img src="map.png" alt="Map"/>
+<!-- A comment. -->
+</body>
+</html>
+"#;
+
+        assert_eq!(
+            inject_kobo_spans(xhtml),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<html xmlns="http://www.w3.org/1999/xhtml">
+<head><title>Chapter 1</title><link rel="stylesheet" href="style.css"/></head>
+<body>
+<h1><span class="koboSpan" id="kobo.1.1">Chapter 1</span></h1>
+<p><span class="koboSpan" id="kobo.2.1">It was cold. </span><span class="koboSpan" id="kobo.2.2">The clocks </span><em><span class="koboSpan" id="kobo.2.3">struck</span></em> <span class="koboSpan" id="kobo.2.4">thirteen!</span></p>
+<img src="map.png" alt="Map"/>
+<!-- A comment. -->
+</body>
+</html>
+"#
+        );
+        assert_eq!(
+            inject_kobo_spans(&inject_kobo_spans(xhtml)),
+            inject_kobo_spans(xhtml)
+        );
+    }
+}
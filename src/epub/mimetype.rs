@@ -0,0 +1,152 @@
+use std::fmt;
+use std::io::{Cursor, Read, Write};
+
+use anyhow::Result;
+use zip::CompressionMethod;
+use zip::write::{ExtendedFileOptions, FileOptions, ZipWriter};
+
+use crate::epub::Epub;
+
+pub const MIMETYPE: &str = "mimetype";
+pub const EPUB_MIMETYPE: &str = "application/epub+zip";
+
+/// A problem found in the `mimetype` entry of an EPUB archive
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MimetypeFinding {
+    /// The archive has no `mimetype` entry
+    Missing,
+    /// The `mimetype` entry is not the first entry of the archive
+    NotFirst,
+    /// The `mimetype` entry is compressed instead of stored
+    Compressed,
+    /// The `mimetype` entry does not declare `application/epub+zip`
+    UnexpectedContent(String),
+}
+
+impl fmt::Display for MimetypeFinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Missing => write!(f, "Missing mimetype entry"),
+            Self::NotFirst => write!(f, "The mimetype entry is not the first archive entry"),
+            Self::Compressed => write!(f, "The mimetype entry is compressed"),
+            Self::UnexpectedContent(content) => {
+                write!(f, "Unexpected mimetype '{}'", content)
+            }
+        }
+    }
+}
+
+/// Checks the contents of the `mimetype` entry, tolerating surrounding
+/// whitespace (e.g. a trailing newline) and differences in casing.
+pub fn is_epub_mimetype(bytes: &[u8]) -> bool {
+    bytes
+        .trim_ascii()
+        .eq_ignore_ascii_case(EPUB_MIMETYPE.as_bytes())
+}
+
+impl Epub {
+    /// Checks that the archive starts with a stored `mimetype` entry declaring
+    /// `application/epub+zip`.
+    pub async fn mimetype_findings(&self) -> Result<Vec<MimetypeFinding>> {
+        let mut archive = self.archive.lock().await;
+        let mut findings = Vec::new();
+
+        let Some(index) = archive.index_for_name(MIMETYPE) else {
+            findings.push(MimetypeFinding::Missing);
+            return Ok(findings);
+        };
+
+        if index != 0 {
+            findings.push(MimetypeFinding::NotFirst);
+        }
+
+        let mut file = archive.by_index(index)?;
+
+        if file.compression() != CompressionMethod::Stored {
+            findings.push(MimetypeFinding::Compressed);
+        }
+
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)?;
+
+        if !is_epub_mimetype(&bytes) {
+            findings.push(MimetypeFinding::UnexpectedContent(
+                String::from_utf8_lossy(&bytes).trim().to_string(),
+            ));
+        }
+
+        Ok(findings)
+    }
+
+    /// Rewrites the archive so it starts with a stored `mimetype` entry
+    /// declaring `application/epub+zip`, as strict readers (e.g. Kobo or
+    /// Adobe Digital Editions) require. Every other entry is copied verbatim,
+    /// in its original order. Returns the bytes of the new archive.
+    ///
+    /// Use [`Epub::mimetype_findings`] to tell whether a fix is needed.
+    pub async fn normalize(&self) -> Result<Vec<u8>> {
+        let mut archive = self.archive.lock().await;
+        let mut zip_writer = ZipWriter::new(Cursor::new(Vec::new()));
+        let options: FileOptions<'_, ExtendedFileOptions> =
+            FileOptions::default().compression_method(CompressionMethod::Stored);
+
+        zip_writer.start_file(MIMETYPE, options)?;
+        zip_writer.write_all(EPUB_MIMETYPE.as_bytes())?;
+
+        for index in 0..archive.len() {
+            let file = archive.by_index_raw(index)?;
+
+            if file.name() != MIMETYPE {
+                zip_writer.raw_copy_file(file)?;
+            }
+        }
+
+        Ok(zip_writer.finish()?.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use anyhow::Result;
+    use zip::CompressionMethod;
+
+    use super::{MimetypeFinding, is_epub_mimetype};
+    use crate::epub::Epub;
+    use crate::epub::test_util::{CONTAINER_XML, EpubBuilder};
+
+    #[tokio::test]
+    async fn accepts_mimetype_with_trailing_newline_and_casing() -> Result<()> {
+        assert!(is_epub_mimetype(b"application/epub+zip"));
+        assert!(is_epub_mimetype(b"application/epub+zip\n"));
+        assert!(is_epub_mimetype(b"application/epub+zip\r\n"));
+        assert!(is_epub_mimetype(b"Application/EPUB+zip "));
+        assert!(!is_epub_mimetype(b"application/zip"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn normalizes_a_compressed_mimetype_out_of_place() -> Result<()> {
+        let epub = EpubBuilder::default()
+            .compression_method(CompressionMethod::Deflated)
+            .entry("META-INF/container.xml", CONTAINER_XML)
+            .entry("mimetype", "application/epub+zip\n")
+            .entry("content.opf", "<package/>")
+            .open()?;
+        assert_eq!(
+            epub.mimetype_findings().await?,
+            [MimetypeFinding::NotFirst, MimetypeFinding::Compressed]
+        );
+
+        let normalized = Epub::from_reader(Cursor::new(epub.normalize().await?))?;
+        assert!(normalized.mimetype_findings().await?.is_empty());
+        assert_eq!(
+            normalized.list_entries().await?,
+            ["mimetype", "META-INF/container.xml", "content.opf"]
+        );
+
+        Ok(())
+    }
+}
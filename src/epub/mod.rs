@@ -1,22 +1,71 @@
 mod container;
 mod content_opf;
+mod css;
+mod direction;
+mod edit;
+mod encryption;
+#[cfg(feature = "online")]
+mod enrich;
+mod fonts;
+mod isbn;
+mod kepub;
+mod mimetype;
+mod opds;
+mod spine;
+mod split;
+mod stats;
+#[cfg(test)]
+pub(crate) mod test_util;
+pub(crate) mod text;
 mod toc;
+mod validate;
 mod writer;
 
 pub use container::{MetaInfContainer, RootFile};
-pub use toc::{Toc, TocMeta};
+pub use content_opf::{Creator, EpubVersion, Identifier, ManifestFinding, Metadata};
+pub use css::{CSS_MAP_FILE, inject_css};
+pub use direction::{Direction, DirectionMismatch, detect_direction};
+pub use encryption::{
+    ADOBE_FONT_ALGORITHM, EncryptedData, Encryption, FontObfuscation, IDPF_FONT_ALGORITHM,
+};
+#[cfg(feature = "online")]
+pub use enrich::{IsbnRecord, lookup_isbn};
+pub use fonts::FontEntry;
+pub use isbn::{is_valid_isbn, normalize_isbn};
+pub use kepub::{KOBO_SPAN_CLASS, inject_kobo_spans};
+pub use mimetype::{EPUB_MIMETYPE, MimetypeFinding, is_epub_mimetype};
+pub use opds::OPDS_LINK_PLACEHOLDER;
+pub use stats::AssetStat;
+pub use text::{TextCount, html_to_text};
+pub use toc::{NavMap, NavPoint, Toc, TocMeta};
+pub use validate::ValidationIssue;
 pub use writer::EpubWriter;
 
-use std::fs::File;
+use std::fmt;
+use std::fs::{File, create_dir_all};
+use std::io::{Read, Seek, copy};
 use std::path::{Path, PathBuf};
 
 use anyhow::Result;
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, OnceCell};
 use zip::ZipArchive;
+use zip::read::ZipFile;
 
 use crate::epub::container::CONTAINER_XML;
-use crate::epub::content_opf::ContentOpf;
-use crate::util::zip::get_file_bytes;
+use crate::epub::content_opf::{ContentOpf, ManifestItem};
+use crate::util::href::resolve_archive_path;
+use crate::util::zip::{get_file_bytes, get_file_reader, safe_join};
+
+/// A seekable byte source the archive of an [`Epub`] is read from
+trait ReadSeek: Read + Seek + Send {}
+
+impl<T: Read + Seek + Send> ReadSeek for T {}
+
+impl fmt::Debug for dyn ReadSeek {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ReadSeek")
+    }
+}
 
 /// Represents an EPUB file and provides access to its components.
 ///
@@ -45,57 +94,304 @@ use crate::util::zip::get_file_bytes;
 /// │       └── font.ttf
 /// └── ...
 /// ```
+///
+/// The `toc.ncx` and OPF files are parsed on first access, so opening a book
+/// only reads `META-INF/container.xml`. Use [`Epub::open_eager`] to parse
+/// them right away.
 #[derive(Debug)]
 pub struct Epub {
-    #[allow(unused)]
-    archive: Mutex<ZipArchive<File>>,
+    /// The file the book was opened from, `None` when read from a reader
+    path: Option<PathBuf>,
+    archive: Mutex<ZipArchive<Box<dyn ReadSeek>>>,
     mic: MetaInfContainer,
-    toc: Toc,
-    content_opf: ContentOpf,
+    toc: OnceCell<Toc>,
+    opf_path: String,
+    content_opf: OnceCell<ContentOpf>,
 }
 
 impl Epub {
     pub fn open<P: AsRef<Path>>(path: P) -> Result<Epub> {
-        let file = File::open(path)?;
-        let mut archive = ZipArchive::new(file)?;
-        let container_xml = get_file_bytes(&mut archive, CONTAINER_XML)?;
-        let mic = MetaInfContainer::new(container_xml)?;
-        let toc_ncx_path = Toc::resolve_toc_ncx_file(&mut archive)?;
-        let toc_ncx = get_file_bytes(&mut archive, &toc_ncx_path)?;
-        let toc = Toc::new(toc_ncx)?;
+        let path = path.as_ref().to_path_buf();
+        let mut epub = Self::from_reader(File::open(&path)?)?;
+
+        epub.path = Some(path);
+        Ok(epub)
+    }
+
+    /// Opens the EPUB like [`Epub::open`], running the blocking file and zip
+    /// I/O on the tokio blocking thread pool so the async runtime isn't
+    /// stalled.
+    pub async fn open_async<P: AsRef<Path>>(path: P) -> Result<Epub> {
+        let path = path.as_ref().to_path_buf();
+
+        tokio::task::spawn_blocking(move || Self::open(path)).await?
+    }
+
+    /// Reads the EPUB from any seekable byte source, e.g. an in-memory
+    /// `Cursor<Vec<u8>>` holding a downloaded book, without going through a
+    /// file. Books read this way can't be edited in place.
+    pub fn from_reader<R: Read + Seek + Send + 'static>(reader: R) -> Result<Epub> {
+        let mut archive = ZipArchive::new(Box::new(reader) as Box<dyn ReadSeek>)?;
+        let mic = MetaInfContainer::from_reader(get_file_reader(&mut archive, CONTAINER_XML)?)?;
         let opf_path = ContentOpf::resolve_opf_file(&mut archive, &mic)?;
-        let content_opf_bytes = get_file_bytes(&mut archive, &opf_path)?;
-        let content_opf = ContentOpf::new(content_opf_bytes)?;
 
         Ok(Epub {
+            path: None,
             archive: Mutex::new(archive),
             mic,
-            toc,
-            content_opf,
+            toc: OnceCell::new(),
+            opf_path,
+            content_opf: OnceCell::new(),
         })
     }
 
+    /// Opens the EPUB parsing the `toc.ncx` and OPF files up front, so
+    /// malformed books fail on open instead of on first access.
+    pub fn open_eager<P: AsRef<Path>>(path: P) -> Result<Epub> {
+        let mut epub = Self::open(path)?;
+        let archive = epub.archive.get_mut();
+        let content_opf = parse_content_opf(archive, &epub.opf_path)?;
+        let toc = parse_toc(archive, &content_opf, &epub.opf_path)?;
+
+        epub.toc = OnceCell::from(toc);
+        epub.content_opf = OnceCell::from(content_opf);
+
+        Ok(epub)
+    }
+
+    /// Extracts every entry of the EPUB at `path` into `outdir`, see
+    /// [`extract_archive`].
     pub fn unpackage<P: AsRef<Path>>(path: P, outdir: P) -> Result<PathBuf> {
         let file = File::open(path)?;
         let mut archive = ZipArchive::new(file)?;
-        archive.extract(&outdir)?;
+        extract_archive(&mut archive, outdir.as_ref())?;
         Ok(outdir.as_ref().to_path_buf())
     }
 
-    /// Returns the `dtb:uid` from the `toc.ncx` file, which is typically the ISBN of the EPUB.
-    pub fn isbn(&self) -> &String {
-        &self.toc.meta.uid
+    /// Lists the names of every entry in the archive, in archive order.
+    pub async fn list_entries(&self) -> Result<Vec<String>> {
+        let archive = self.archive.lock().await;
+
+        Ok(archive.file_names().map(String::from).collect())
     }
 
-    pub fn toc(&self) -> &Toc {
-        &self.toc
+    /// Extracts a single archive entry into `dest`, keeping its path relative
+    /// to the archive root.
+    pub async fn extract_entry(&self, name: &str, dest: &Path) -> Result<()> {
+        let mut archive = self.archive.lock().await;
+
+        extract_file(archive.by_name(name)?, dest)
+    }
+
+    /// Returns the ISBN of the book, the first OPF `dc:identifier` holding a
+    /// valid ISBN-10 or ISBN-13. Falls back to the `dtb:uid` of the `toc.ncx`
+    /// file, which is often but not always the ISBN, when there is none.
+    pub async fn isbn(&self) -> Result<String> {
+        if let Some(isbn) = self.content_opf().await?.isbn() {
+            return Ok(isbn);
+        }
+
+        Ok(self.toc().await?.meta.uid.clone())
+    }
+
+    /// Returns every `dc:identifier` of the OPF file, in document order
+    pub async fn identifiers(&self) -> Result<&[Identifier]> {
+        Ok(&self.content_opf().await?.metadata.identifiers)
+    }
+
+    /// Returns the table of contents, parsing it on first access.
+    ///
+    /// It is read from the `toc.ncx` file, falling back to the EPUB3
+    /// navigation document for books without one. Books with neither get an
+    /// empty table of contents, so their metadata is still readable.
+    pub async fn toc(&self) -> Result<&Toc> {
+        self.toc
+            .get_or_try_init(|| async {
+                let content_opf = self.content_opf().await?;
+                let mut archive = self.archive.lock().await;
+                parse_toc(&mut archive, content_opf, &self.opf_path)
+            })
+            .await
     }
 
     pub fn mic(&self) -> &MetaInfContainer {
         &self.mic
     }
 
-    pub fn content_opf(&self) -> &ContentOpf {
-        &self.content_opf
+    /// Returns the parsed OPF file, parsing it on first access.
+    pub async fn content_opf(&self) -> Result<&ContentOpf> {
+        self.content_opf
+            .get_or_try_init(|| async {
+                let mut archive = self.archive.lock().await;
+                parse_content_opf(&mut archive, &self.opf_path)
+            })
+            .await
+    }
+
+    /// Returns the EPUB version declared by the OPF file
+    pub async fn version(&self) -> Result<EpubVersion> {
+        Ok(self.content_opf().await?.epub_version())
+    }
+
+    /// Returns the path of the OPF file in the archive
+    pub fn opf_path(&self) -> &str {
+        &self.opf_path
+    }
+
+    /// Resolves a manifest `href`, which is relative to the OPF file, into a
+    /// path in the archive. See [`resolve_archive_path`].
+    pub(crate) fn resolve_href(&self, href: &str) -> String {
+        resolve_archive_path(&self.opf_path, href)
+    }
+
+    /// Checks whether the EPUB is a Kobo-enhanced (KEPUB) book by looking for
+    /// `koboSpan` markup in its content documents.
+    pub async fn is_kepub(&self) -> Result<bool> {
+        let mut archive = self.archive.lock().await;
+        let content_documents = archive
+            .file_names()
+            .filter(|name| {
+                let name = name.to_lowercase();
+                name.ends_with(".xhtml") || name.ends_with(".html") || name.ends_with(".htm")
+            })
+            .map(String::from)
+            .collect::<Vec<String>>();
+
+        for name in content_documents {
+            let bytes = get_file_bytes(&mut archive, &name)?;
+
+            if bytes
+                .windows(KOBO_SPAN_CLASS.len())
+                .any(|window| window == KOBO_SPAN_CLASS.as_bytes())
+            {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+}
+
+fn parse_toc<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    content_opf: &ContentOpf,
+    opf_path: &str,
+) -> Result<Toc> {
+    let manifest_path = |item: &ManifestItem| resolve_archive_path(opf_path, &item.href);
+    let ncx_path = Toc::resolve_toc_ncx_file(archive).ok().or_else(|| {
+        content_opf
+            .ncx_item()
+            .map(manifest_path)
+            .filter(|path| archive.index_for_name(path).is_some())
+    });
+
+    if let Some(ncx_path) = ncx_path {
+        return Toc::from_reader(get_file_reader(archive, &ncx_path)?);
+    }
+
+    let mut toc = match content_opf.nav_item() {
+        Some(item) if archive.index_for_name(&manifest_path(item)).is_some() => {
+            Toc::from_nav_xhtml(get_file_reader(archive, &manifest_path(item))?, &item.href)?
+        }
+        _ => Toc::default(),
+    };
+
+    toc.meta.uid = content_opf.primary_identifier().to_string();
+
+    Ok(toc)
+}
+
+fn parse_content_opf<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    opf_path: &str,
+) -> Result<ContentOpf> {
+    ContentOpf::from_reader(get_file_reader(archive, opf_path)?)
+}
+
+/// Extracts every entry of `archive` into `dest`, creating the intermediate
+/// directories. Entries are copied byte for byte, so the `mimetype` file is
+/// kept verbatim.
+///
+/// Entry names are all checked before anything is written: archives with an
+/// entry that would land outside of `dest` (zip-slip), through `..`
+/// components or an absolute path, are rejected as a whole.
+pub(crate) fn extract_archive<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    dest: &Path,
+) -> Result<()> {
+    for index in 0..archive.len() {
+        safe_join(dest, archive.by_index_raw(index)?.name())?;
+    }
+
+    for index in 0..archive.len() {
+        extract_file(archive.by_index(index)?, dest)?;
+    }
+
+    Ok(())
+}
+
+/// Extracts a single archive entry into `dest`, keeping its path relative to
+/// the archive root.
+fn extract_file<R: Read>(mut file: ZipFile<'_, R>, dest: &Path) -> Result<()> {
+    let outpath = safe_join(dest, file.name())?;
+
+    if file.is_dir() {
+        create_dir_all(&outpath)?;
+        return Ok(());
+    }
+
+    if let Some(parent) = outpath.parent() {
+        create_dir_all(parent)?;
+    }
+
+    copy(&mut file, &mut File::create(&outpath)?)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs::{create_dir_all, read, remove_dir_all};
+
+    use anyhow::Result;
+
+    use crate::epub::Epub;
+    use crate::epub::test_util::EpubBuilder;
+
+    #[tokio::test]
+    async fn unpackages_nested_entries_and_rejects_zip_slip() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("bookworm-unpackage-{}", std::process::id()));
+        create_dir_all(&dir)?;
+
+        EpubBuilder::default()
+            .entry("mimetype", "application/epub+zip")
+            .entry("META-INF/container.xml", "<container/>")
+            .entry("OEBPS/content.opf", "<package/>")
+            .entry("OEBPS/Text/Part 1/ch1.xhtml", "<html/>")
+            .write(&dir.join("book.epub"))?;
+        Epub::unpackage(dir.join("book.epub"), dir.join("book"))?;
+
+        assert_eq!(read(dir.join("book/mimetype"))?, b"application/epub+zip");
+        assert_eq!(
+            read(dir.join("book/META-INF/container.xml"))?,
+            b"<container/>"
+        );
+        assert!(dir.join("book/OEBPS/content.opf").is_file());
+        assert!(dir.join("book/OEBPS/Text/Part 1/ch1.xhtml").is_file());
+
+        for name in ["../evil.txt", "OEBPS/../../evil.txt", "/tmp/evil.txt"] {
+            EpubBuilder::default()
+                .entry("mimetype", "application/epub+zip")
+                .entry(name, "evil")
+                .write(&dir.join("evil.epub"))?;
+
+            assert!(Epub::unpackage(dir.join("evil.epub"), dir.join("evil")).is_err());
+            assert!(!dir.join("evil").exists());
+            assert!(!dir.join("evil.txt").exists());
+        }
+
+        remove_dir_all(dir)?;
+
+        Ok(())
     }
 }
@@ -0,0 +1,130 @@
+use anyhow::Result;
+use chrono::{DateTime, NaiveDate, SecondsFormat};
+use xml::escape::{escape_str_attribute, escape_str_pcdata};
+
+use crate::epub::Epub;
+
+/// Placeholder used as the acquisition link `href` of OPDS entries, meant to
+/// be replaced by the catalog with the actual download URL.
+pub const OPDS_LINK_PLACEHOLDER: &str = "{href}";
+
+/// Atom `updated` date of entries for books declaring neither a modification
+/// nor a publication date, fixed so rendering the same book is reproducible
+const OPDS_UPDATED_FALLBACK: &str = "1970-01-01T00:00:00Z";
+
+impl Epub {
+    /// Renders an Atom/OPDS `entry` fragment for this EPUB based on its OPF
+    /// metadata.
+    ///
+    /// The acquisition link points to [`OPDS_LINK_PLACEHOLDER`], so entries can
+    /// be stitched into a catalog once the download URL is known. The Atom
+    /// `updated` date is the `dcterms:modified` date, else the `dc:date`.
+    pub async fn to_opds_entry(&self) -> Result<String> {
+        let content_opf = self.content_opf().await?;
+        let metadata = &content_opf.metadata;
+        let identifier = escape_str_pcdata(content_opf.primary_identifier());
+        let authors = metadata
+            .creators
+            .iter()
+            .map(|creator| {
+                format!(
+                    "  <author>\n    <name>{}</name>\n  </author>\n",
+                    escape_str_pcdata(&creator.name)
+                )
+            })
+            .collect::<String>();
+        let updated = [&metadata.modified, &metadata.date]
+            .into_iter()
+            .find_map(|date| date.as_deref().and_then(atom_date))
+            .unwrap_or_else(|| OPDS_UPDATED_FALLBACK.to_string());
+
+        Ok(format!(
+            r#"<entry xmlns="http://www.w3.org/2005/Atom" xmlns:dc="http://purl.org/dc/terms/">
+  <title>{title}</title>
+{authors}  <id>{identifier}</id>
+  <updated>{updated}</updated>
+  <dc:identifier>{identifier}</dc:identifier>
+  <dc:language>{language}</dc:language>
+  <link rel="http://opds-spec.org/acquisition" href="{href}" type="application/epub+zip"/>
+</entry>"#,
+            title = escape_str_pcdata(&metadata.title),
+            authors = authors,
+            identifier = identifier,
+            updated = updated,
+            language = escape_str_pcdata(&metadata.language),
+            href = escape_str_attribute(OPDS_LINK_PLACEHOLDER),
+        ))
+    }
+}
+
+/// Formats an OPF date as an RFC 3339 timestamp, as Atom requires. The
+/// `YYYY`, `YYYY-MM` and `YYYY-MM-DD` forms allowed by `dc:date` are taken
+/// as midnight UTC of their first day.
+fn atom_date(value: &str) -> Option<String> {
+    let value = value.trim();
+
+    if let Ok(date_time) = DateTime::parse_from_rfc3339(value) {
+        return Some(date_time.to_rfc3339_opts(SecondsFormat::Secs, true));
+    }
+
+    let date = match value.len() {
+        4 => format!("{}-01-01", value),
+        7 => format!("{}-01", value),
+        _ => value.to_string(),
+    };
+
+    NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+        .ok()
+        .map(|date| format!("{}T00:00:00Z", date.format("%Y-%m-%d")))
+}
+
+#[cfg(test)]
+mod test {
+    use anyhow::Result;
+
+    use crate::epub::test_util::{EpubBuilder, content_opf};
+
+    async fn updated(metadata: &str) -> Result<String> {
+        let metadata = format!(
+            r#"<dc:identifier id="BookId">urn:uuid:1234</dc:identifier>
+        <dc:title>Updates</dc:title>
+        {}"#,
+            metadata
+        );
+        let entry = EpubBuilder::new(&content_opf(&metadata, &[]))
+            .open()?
+            .to_opds_entry()
+            .await?;
+        let start = entry.find("<updated>").map_or(0, |start| start + 9);
+        let end = entry.find("</updated>").unwrap_or(start);
+
+        Ok(entry[start..end].to_string())
+    }
+
+    #[tokio::test]
+    async fn dates_entries_after_the_book() -> Result<()> {
+        assert_eq!(
+            updated(
+                r#"<dc:date>2020-05-01</dc:date>
+        <meta property="dcterms:modified">2021-02-03T04:05:06Z</meta>"#
+            )
+            .await?,
+            "2021-02-03T04:05:06Z"
+        );
+        assert_eq!(
+            updated("<dc:date>2020-05-01</dc:date>").await?,
+            "2020-05-01T00:00:00Z"
+        );
+        assert_eq!(
+            updated("<dc:date>2020</dc:date>").await?,
+            "2020-01-01T00:00:00Z"
+        );
+        assert_eq!(
+            updated("<dc:date>Spring 2020</dc:date>").await?,
+            "1970-01-01T00:00:00Z"
+        );
+        assert_eq!(updated("").await?, "1970-01-01T00:00:00Z");
+
+        Ok(())
+    }
+}
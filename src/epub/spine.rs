@@ -0,0 +1,35 @@
+use anyhow::{Result, anyhow};
+
+use crate::epub::Epub;
+use crate::util::zip::get_file_bytes;
+
+impl Epub {
+    /// Returns the number of documents in the spine
+    pub async fn spine_len(&self) -> Result<usize> {
+        Ok(self.content_opf().await?.spine.len())
+    }
+
+    /// Reads the content document at `index` in the spine (reading order),
+    /// resolving its manifest `href` against the OPF directory.
+    pub async fn spine_document(&self, index: usize) -> Result<Vec<u8>> {
+        let content_opf = self.content_opf().await?;
+        let spine_item = content_opf.spine.get(index).ok_or_else(|| {
+            anyhow!(
+                "Spine index {} is out of bounds, the spine has {} documents",
+                index,
+                content_opf.spine.len()
+            )
+        })?;
+        let item = content_opf
+            .manifest_item(&spine_item.idref)
+            .ok_or_else(|| {
+                anyhow!(
+                    "Spine item '{}' is not declared in the manifest",
+                    spine_item.idref
+                )
+            })?;
+        let mut archive = self.archive.lock().await;
+
+        get_file_bytes(&mut archive, &self.resolve_href(&item.href))
+    }
+}
@@ -0,0 +1,396 @@
+use std::collections::HashSet;
+use std::fs::{File, create_dir_all};
+use std::io::{Read, Seek, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use xml::escape::{escape_str_attribute, escape_str_pcdata};
+use zip::write::{SimpleFileOptions, ZipWriter};
+use zip::{CompressionMethod, ZipArchive};
+
+use crate::epub::container::CONTAINER_XML;
+use crate::epub::content_opf::{ContentOpf, ManifestItem, NCX_MEDIA_TYPE};
+use crate::epub::encryption::ENCRYPTION_XML;
+use crate::epub::toc::DocTitle;
+use crate::epub::{Epub, NavMap, NavPoint, Toc, TocMeta};
+use crate::util::fs::sanitize_file_name;
+use crate::util::href::resolve_archive_path;
+use crate::util::zip::get_file_bytes;
+
+const SPLIT_NCX_HREF: &str = "toc.ncx";
+
+fn is_content_document(item: &ManifestItem) -> bool {
+    matches!(
+        item.media_type.as_str(),
+        "application/xhtml+xml" | "text/html" | NCX_MEDIA_TYPE
+    )
+}
+
+/// Collects the URLs an (X)HTML document or a stylesheet references: the
+/// `src` and `href` attribute values (`xlink:href` included), the CSS
+/// `url()` values and the `@import` strings.
+fn referenced_urls(text: &str) -> Vec<&str> {
+    let mut urls = Vec::new();
+
+    for attribute in ["src", "href"] {
+        let mut rest = text;
+
+        while let Some(index) = rest.find(attribute) {
+            let before = rest[..index].chars().next_back();
+            rest = &rest[index + attribute.len()..];
+
+            // Skips longer names ending the same, e.g. `data-src`
+            if !before.is_some_and(|c| c.is_whitespace() || c == ':') {
+                continue;
+            }
+
+            if let Some(value) = rest.trim_start().strip_prefix('=') {
+                urls.extend(quoted(value.trim_start()));
+            }
+        }
+    }
+
+    for (index, _) in text.match_indices("url(") {
+        let value = text[index + 4..].trim_start();
+
+        match quoted(value) {
+            Some(url) => urls.push(url),
+            None => urls.extend(value.split(')').next().map(str::trim_end)),
+        }
+    }
+
+    for (index, _) in text.match_indices("@import") {
+        urls.extend(quoted(text[index + 7..].trim_start()));
+    }
+
+    urls
+}
+
+/// Returns the contents of the quoted string `value` starts with
+fn quoted(value: &str) -> Option<&str> {
+    let quote = value.chars().next().filter(|c| matches!(c, '"' | '\''))?;
+    let value = &value[1..];
+
+    value.find(quote).map(|end| &value[..end])
+}
+
+fn find_label<'a>(nav_points: &'a [NavPoint], href: &str) -> Option<&'a str> {
+    nav_points.iter().find_map(|nav_point| {
+        let src = nav_point.content_src.split('#').next().unwrap_or_default();
+
+        if src == href {
+            Some(nav_point.label.as_str())
+        } else {
+            find_label(&nav_point.children, href)
+        }
+    })
+}
+
+impl Epub {
+    /// Splits the book into one standalone EPUB per spine document, written
+    /// into `outdir` and named after the chapter titles.
+    ///
+    /// Each EPUB carries the parent metadata with the chapter title appended,
+    /// its content document and the resources it references (stylesheets,
+    /// images, and the fonts and images the stylesheets reference), keeping
+    /// the original archive layout. Manifest items missing from the archive
+    /// are left out, and so are spine documents.
+    pub async fn split<P: AsRef<Path>>(&self, outdir: P) -> Result<Vec<PathBuf>> {
+        let outdir = outdir.as_ref();
+        let content_opf = self.content_opf().await?;
+        let toc = self.toc().await?;
+        let metadata = &content_opf.metadata;
+        let resources = content_opf
+            .manifest
+            .iter()
+            .filter(|item| !is_content_document(item))
+            .collect::<Vec<&ManifestItem>>();
+        let mut archive = self.archive.lock().await;
+        let has_encryption = archive.index_for_name(ENCRYPTION_XML).is_some();
+        let stored = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+        let deflated = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+        let mut outputs = Vec::new();
+
+        create_dir_all(outdir)?;
+
+        for (index, spine_item) in content_opf.spine.iter().enumerate() {
+            let Some(document) = content_opf.manifest_item(&spine_item.idref).filter(|item| {
+                archive
+                    .index_for_name(&self.resolve_href(&item.href))
+                    .is_some()
+            }) else {
+                continue;
+            };
+
+            let chapter_title = find_label(&toc.nav_map.nav_points, &document.href)
+                .map(|label| label.trim())
+                .filter(|label| !label.is_empty())
+                .map(String::from)
+                .unwrap_or_else(|| format!("Part {}", index + 1));
+            let title = format!("{} - {}", metadata.title, chapter_title);
+            let file_name = format!(
+                "{:02} - {}.epub",
+                index + 1,
+                sanitize_file_name(&chapter_title)
+            );
+            let path = outdir.join(file_name);
+            let mut zip_writer = ZipWriter::new(File::create(&path)?);
+
+            zip_writer.start_file("mimetype", stored)?;
+            zip_writer.write_all(b"application/epub+zip")?;
+            zip_writer.raw_copy_file(archive.by_name(CONTAINER_XML)?)?;
+
+            if has_encryption {
+                zip_writer.raw_copy_file(archive.by_name(ENCRYPTION_XML)?)?;
+            }
+
+            let resources = self.referenced_resources(&mut archive, &resources, document)?;
+            let mut written = HashSet::new();
+
+            for item in resources.iter().copied().chain([document]) {
+                let item_path = self.resolve_href(&item.href);
+
+                if written.insert(item_path.clone()) {
+                    zip_writer.raw_copy_file(archive.by_name(&item_path)?)?;
+                }
+            }
+
+            let split_toc = Toc {
+                meta: TocMeta {
+                    uid: content_opf.primary_identifier().to_string(),
+                },
+                doc_title: DocTitle {
+                    title: title.clone(),
+                },
+                nav_map: NavMap {
+                    nav_points: vec![NavPoint {
+                        label: chapter_title,
+                        content_src: document.href.clone(),
+                        play_order: 1,
+                        children: Vec::new(),
+                    }],
+                },
+            };
+
+            zip_writer.start_file(self.resolve_href(SPLIT_NCX_HREF), deflated)?;
+            zip_writer.write_all(split_toc.to_ncx().as_bytes())?;
+            zip_writer.start_file(&self.opf_path, deflated)?;
+            zip_writer
+                .write_all(split_opf(content_opf, &title, &resources, document).as_bytes())?;
+            zip_writer.finish()?;
+
+            outputs.push(path);
+        }
+
+        Ok(outputs)
+    }
+
+    /// Finds the `resources` the content `document` references, directly or
+    /// through its stylesheets, which are present in the archive
+    fn referenced_resources<'a, R: Read + Seek>(
+        &self,
+        archive: &mut ZipArchive<R>,
+        resources: &[&'a ManifestItem],
+        document: &ManifestItem,
+    ) -> Result<Vec<&'a ManifestItem>> {
+        let mut referenced = Vec::new();
+        let mut pending = vec![self.resolve_href(&document.href)];
+
+        while let Some(path) = pending.pop() {
+            let text = String::from_utf8_lossy(&get_file_bytes(archive, &path)?).to_string();
+
+            for url in referenced_urls(&text) {
+                if url.contains(':') {
+                    continue; // Remote, `data:` and `mailto:` URLs
+                }
+
+                let target = resolve_archive_path(&path, url);
+                let Some(item) = resources
+                    .iter()
+                    .find(|item| self.resolve_href(&item.href) == target)
+                else {
+                    continue;
+                };
+
+                if referenced
+                    .iter()
+                    .any(|found: &&ManifestItem| found.id == item.id)
+                    || archive.index_for_name(&target).is_none()
+                {
+                    continue;
+                }
+
+                referenced.push(*item);
+
+                if item.media_type == "text/css" {
+                    pending.push(target);
+                }
+            }
+        }
+
+        Ok(referenced)
+    }
+}
+
+fn split_opf(
+    content_opf: &ContentOpf,
+    title: &str,
+    resources: &[&ManifestItem],
+    document: &ManifestItem,
+) -> String {
+    let metadata = &content_opf.metadata;
+    let creators = metadata
+        .creators
+        .iter()
+        .map(|creator| {
+            let mut attributes = String::new();
+
+            if let Some(role) = &creator.role {
+                attributes.push_str(&format!(" opf:role=\"{}\"", escape_str_attribute(role)));
+            }
+
+            if let Some(file_as) = &creator.file_as {
+                attributes.push_str(&format!(
+                    " opf:file-as=\"{}\"",
+                    escape_str_attribute(file_as)
+                ));
+            }
+
+            format!(
+                "    <dc:creator{}>{}</dc:creator>\n",
+                attributes,
+                escape_str_pcdata(&creator.name)
+            )
+        })
+        .collect::<String>();
+    let items = resources
+        .iter()
+        .copied()
+        .chain([document])
+        .map(|item| {
+            format!(
+                "    <item id=\"{}\" href=\"{}\" media-type=\"{}\"/>\n",
+                escape_str_attribute(&item.id),
+                escape_str_attribute(&item.href),
+                escape_str_attribute(&item.media_type)
+            )
+        })
+        .collect::<String>();
+
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="2.0" unique-identifier="BookId">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:opf="http://www.idpf.org/2007/opf">
+    <dc:title>{title}</dc:title>
+{creators}    <dc:language>{language}</dc:language>
+    <dc:identifier id="BookId">{identifier}</dc:identifier>
+  </metadata>
+  <manifest>
+    <item id="ncx" href="{ncx}" media-type="{ncx_media_type}"/>
+{items}  </manifest>
+  <spine toc="ncx">
+    <itemref idref="{idref}"/>
+  </spine>
+</package>
+"#,
+        title = escape_str_pcdata(title),
+        creators = creators,
+        language = escape_str_pcdata(&metadata.language),
+        identifier = escape_str_pcdata(content_opf.primary_identifier()),
+        ncx = SPLIT_NCX_HREF,
+        ncx_media_type = NCX_MEDIA_TYPE,
+        items = items,
+        idref = escape_str_attribute(&document.id),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs::remove_dir_all;
+
+    use anyhow::Result;
+
+    use crate::epub::Epub;
+    use crate::epub::test_util::{EpubBuilder, content_opf};
+
+    #[tokio::test]
+    async fn copies_only_the_resources_each_chapter_references() -> Result<()> {
+        let outdir = std::env::temp_dir().join(format!("bookworm-split-{}", std::process::id()));
+        let epub = EpubBuilder::new(&content_opf(
+            "<dc:title>Split</dc:title>
+        <dc:identifier id=\"BookId\">urn:uuid:1234</dc:identifier>",
+            &[
+                ("ch1", "Text/ch1.xhtml", "application/xhtml+xml"),
+                ("ch2", "Text/ch2.xhtml", "application/xhtml+xml"),
+                ("css", "Styles/style.css", "text/css"),
+                ("font", "Fonts/serif.otf", "font/otf"),
+                ("img1", "Images/one.jpg", "image/jpeg"),
+                ("img2", "Images/two%20b.jpg", "image/jpeg"),
+                ("missing", "Images/missing.png", "image/png"),
+                ("ncx", "toc.ncx", "application/x-dtbncx+xml"),
+            ],
+        ))
+        .entry(
+            "toc.ncx",
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<ncx xmlns="http://www.daisy.org/z3986/2005/ncx/" version="2005-1">
+    <navMap>
+        <navPoint id="ch1" playOrder="1">
+            <navLabel><text>Who? "Me": A|B</text></navLabel>
+            <content src="Text/ch1.xhtml"/>
+        </navPoint>
+        <navPoint id="ch2" playOrder="2">
+            <navLabel><text> </text></navLabel>
+            <content src="Text/ch2.xhtml"/>
+        </navPoint>
+    </navMap>
+</ncx>
+"#,
+        )
+        .entry(
+            "Text/ch1.xhtml",
+            r#"<html><head><link rel="stylesheet" href="../Styles/style.css"/></head>
+<body><img src="../Images/one.jpg"/><img data-src="../Images/two%20b.jpg"/></body></html>"#,
+        )
+        .entry(
+            "Text/ch2.xhtml",
+            r#"<html><body><img src='../Images/two%20b.jpg'/><img src="../Images/missing.png"/>
+<a href="ch1.xhtml#top">Back</a></body></html>"#,
+        )
+        .entry(
+            "Styles/style.css",
+            r#"@font-face { src: url("../Fonts/serif.otf"); }"#,
+        )
+        .entry("Fonts/serif.otf", "font")
+        .entry("Images/one.jpg", "one")
+        .entry("Images/two b.jpg", "two")
+        .open()?;
+
+        let outputs = epub.split(&outdir).await?;
+        let mut entries = Vec::new();
+
+        for output in &outputs {
+            entries.push(Epub::open(output)?.list_entries().await?);
+        }
+
+        remove_dir_all(&outdir)?;
+
+        assert_eq!(
+            outputs,
+            [
+                outdir.join("01 - Who- -Me-- A-B.epub"),
+                outdir.join("02 - Part 2.epub")
+            ]
+        );
+        assert!(entries[0].contains(&"Styles/style.css".to_string()));
+        assert!(entries[0].contains(&"Fonts/serif.otf".to_string()));
+        assert!(entries[0].contains(&"Images/one.jpg".to_string()));
+        assert!(!entries[0].contains(&"Images/two b.jpg".to_string()));
+        assert!(entries[1].contains(&"Images/two b.jpg".to_string()));
+        assert!(!entries[1].contains(&"Images/one.jpg".to_string()));
+        assert!(!entries[1].contains(&"Styles/style.css".to_string()));
+        assert!(!entries[1].contains(&"Text/ch1.xhtml".to_string()));
+
+        Ok(())
+    }
+}
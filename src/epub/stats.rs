@@ -0,0 +1,90 @@
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+
+use crate::epub::Epub;
+
+/// Number and size of the manifest items sharing a media type
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssetStat {
+    pub media_type: String,
+    pub count: usize,
+    /// Sum of the uncompressed sizes in bytes of the items in the archive
+    pub total_bytes: u64,
+}
+
+impl Epub {
+    /// Groups the manifest items by media type, with their uncompressed size
+    /// taken from the archive. Items missing from the archive are left out.
+    /// Sorted by total size, largest first.
+    pub async fn asset_summary(&self) -> Result<Vec<AssetStat>> {
+        let content_opf = self.content_opf().await?;
+        let mut archive = self.archive.lock().await;
+        let mut stats = BTreeMap::<&str, AssetStat>::new();
+
+        for item in &content_opf.manifest {
+            let Ok(file) = archive.by_name(&self.resolve_href(&item.href)) else {
+                continue;
+            };
+            let stat = stats
+                .entry(item.media_type.as_str())
+                .or_insert_with(|| AssetStat {
+                    media_type: item.media_type.clone(),
+                    count: 0,
+                    total_bytes: 0,
+                });
+
+            stat.count += 1;
+            stat.total_bytes += file.size();
+        }
+
+        let mut stats = stats.into_values().collect::<Vec<AssetStat>>();
+        stats.sort_by_key(|stat| Reverse(stat.total_bytes));
+
+        Ok(stats)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use anyhow::Result;
+
+    use super::AssetStat;
+    use crate::epub::test_util::{EpubBuilder, content_opf};
+
+    #[tokio::test]
+    async fn sums_manifest_items_by_media_type() -> Result<()> {
+        let epub = EpubBuilder::new(&content_opf(
+            "<dc:identifier id=\"BookId\">urn:uuid:1234</dc:identifier>",
+            &[
+                ("ch1", "Text/ch1.xhtml", "application/xhtml+xml"),
+                ("ch2", "Text/ch2.xhtml", "application/xhtml+xml"),
+                ("cover", "Images/cover.jpg", "image/jpeg"),
+                ("missing", "Images/missing.png", "image/png"),
+            ],
+        ))
+        .entry("Text/ch1.xhtml", "<html>one</html>")
+        .entry("Text/ch2.xhtml", "<html>two!</html>")
+        .entry("Images/cover.jpg", [0u8; 1000])
+        .open()?;
+
+        assert_eq!(
+            epub.asset_summary().await?,
+            vec![
+                AssetStat {
+                    media_type: "image/jpeg".to_string(),
+                    count: 1,
+                    total_bytes: 1000,
+                },
+                AssetStat {
+                    media_type: "application/xhtml+xml".to_string(),
+                    count: 2,
+                    total_bytes: 33,
+                },
+            ]
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,110 @@
+use std::fs::write;
+use std::io::{Cursor, Write};
+use std::path::Path;
+
+use anyhow::Result;
+use zip::CompressionMethod;
+use zip::write::{ExtendedFileOptions, FileOptions, ZipWriter};
+
+use crate::epub::Epub;
+
+/// A `META-INF/container.xml` pointing at `content.opf`, at the archive root
+pub(crate) const CONTAINER_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+    <rootfiles>
+        <rootfile full-path="content.opf" media-type="application/oebps-package+xml"/>
+    </rootfiles>
+</container>
+"#;
+
+/// Renders a `content.opf` with the `metadata` children and a manifest item
+/// for each `(id, href, media_type)`. XHTML items make up the spine, in
+/// order. The package `unique-identifier` references the `BookId` id.
+pub(crate) fn content_opf(metadata: &str, manifest: &[(&str, &str, &str)]) -> String {
+    let items = manifest
+        .iter()
+        .map(|(id, href, media_type)| {
+            format!(
+                r#"        <item id="{}" href="{}" media-type="{}"/>
+"#,
+                id, href, media_type
+            )
+        })
+        .collect::<String>();
+    let itemrefs = manifest
+        .iter()
+        .filter(|(_, _, media_type)| *media_type == "application/xhtml+xml")
+        .map(|(id, _, _)| format!("        <itemref idref=\"{}\"/>\n", id))
+        .collect::<String>();
+
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="3.0" unique-identifier="BookId">
+    <metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+        {}
+    </metadata>
+    <manifest>
+{}    </manifest>
+    <spine>
+{}    </spine>
+</package>
+"#,
+        metadata, items, itemrefs
+    )
+}
+
+/// Builds a ZIP archive in memory, entry by entry and in order
+#[derive(Default)]
+pub(crate) struct EpubBuilder {
+    entries: Vec<(String, Vec<u8>)>,
+    compression_method: Option<CompressionMethod>,
+}
+
+impl EpubBuilder {
+    /// Starts an EPUB with the `mimetype`, a `container.xml` and the
+    /// `content.opf` at the archive root
+    pub(crate) fn new(content_opf: &str) -> Self {
+        Self::default()
+            .entry("mimetype", "application/epub+zip")
+            .entry("META-INF/container.xml", CONTAINER_XML)
+            .entry("content.opf", content_opf)
+    }
+
+    pub(crate) fn entry(mut self, name: &str, contents: impl AsRef<[u8]>) -> Self {
+        self.entries
+            .push((name.to_string(), contents.as_ref().to_vec()));
+        self
+    }
+
+    /// Compresses every entry with `compression_method`, instead of the
+    /// `zip` crate default
+    pub(crate) fn compression_method(mut self, compression_method: CompressionMethod) -> Self {
+        self.compression_method = Some(compression_method);
+        self
+    }
+
+    pub(crate) fn build(&self) -> Result<Vec<u8>> {
+        let mut zip_writer = ZipWriter::new(Cursor::new(Vec::new()));
+        let mut options: FileOptions<'_, ExtendedFileOptions> = FileOptions::default();
+
+        if let Some(compression_method) = self.compression_method {
+            options = options.compression_method(compression_method);
+        }
+
+        for (name, contents) in &self.entries {
+            zip_writer.start_file(name.as_str(), options.clone())?;
+            zip_writer.write_all(contents)?;
+        }
+
+        Ok(zip_writer.finish()?.into_inner())
+    }
+
+    pub(crate) fn open(&self) -> Result<Epub> {
+        Epub::from_reader(Cursor::new(self.build()?))
+    }
+
+    pub(crate) fn write(&self, path: &Path) -> Result<()> {
+        write(path, self.build()?)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,365 @@
+use std::ops::AddAssign;
+
+use anyhow::Result;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::epub::Epub;
+use crate::util::zip::get_file_bytes;
+
+/// Elements starting a new paragraph in the extracted text
+pub(crate) const BLOCK_ELEMENTS: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "dd",
+    "div",
+    "dl",
+    "dt",
+    "figcaption",
+    "figure",
+    "footer",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hr",
+    "li",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "table",
+    "tr",
+    "ul",
+];
+
+/// Elements whose content is never part of the prose
+const SKIPPED_ELEMENTS: &[&str] = &["head", "script", "style"];
+
+impl Epub {
+    /// Extracts the plain text of the book, following the spine order and
+    /// skipping `linear="no"` documents. Paragraphs are separated by a blank
+    /// line and so are documents.
+    pub async fn text(&self) -> Result<String> {
+        let texts = self
+            .spine_texts()
+            .await?
+            .into_iter()
+            .map(|(_, text)| text)
+            .collect::<Vec<String>>();
+
+        Ok(texts.join("\n\n"))
+    }
+
+    /// Extracts the plain text of each linear spine document, in reading
+    /// order, along with the document manifest `href`. Fails on
+    /// DRM-protected books, see [`Epub::is_drm_protected`].
+    pub async fn spine_texts(&self) -> Result<Vec<(String, String)>> {
+        self.ensure_not_drm_protected().await?;
+
+        let content_opf = self.content_opf().await?;
+        let mut archive = self.archive.lock().await;
+        let mut texts = Vec::new();
+
+        for spine_item in content_opf.spine.iter().filter(|item| item.linear) {
+            let Some(item) = content_opf.manifest_item(&spine_item.idref) else {
+                continue;
+            };
+
+            let bytes = get_file_bytes(&mut archive, &self.resolve_href(&item.href))?;
+            texts.push((
+                item.href.clone(),
+                html_to_text(&String::from_utf8_lossy(&bytes)),
+            ));
+        }
+
+        Ok(texts)
+    }
+
+    /// Counts the words of the book text, see [`TextCount`]
+    pub async fn word_count(&self) -> Result<usize> {
+        Ok(TextCount::of(&self.text().await?).words)
+    }
+
+    /// Counts the characters of the book text, see [`TextCount`]
+    pub async fn character_count(&self) -> Result<usize> {
+        Ok(TextCount::of(&self.text().await?).characters)
+    }
+}
+
+/// Word and character counts of a text
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TextCount {
+    /// Words split on Unicode word boundaries (UAX #29), so each CJK
+    /// ideograph counts as a word of its own
+    pub words: usize,
+    /// User-perceived characters (grapheme clusters), whitespace excluded
+    pub characters: usize,
+}
+
+impl TextCount {
+    pub fn of(text: &str) -> Self {
+        TextCount {
+            words: text.unicode_words().count(),
+            characters: text
+                .graphemes(true)
+                .filter(|grapheme| !grapheme.chars().all(char::is_whitespace))
+                .count(),
+        }
+    }
+}
+
+impl AddAssign for TextCount {
+    fn add_assign(&mut self, other: Self) {
+        self.words += other.words;
+        self.characters += other.characters;
+    }
+}
+
+/// Converts an (X)HTML document into plain text: tags are dropped, entities
+/// decoded, whitespace collapsed and block elements turned into paragraphs.
+pub fn html_to_text(html: &str) -> String {
+    html_to_blocks(html)
+        .into_iter()
+        .map(|block| block.text)
+        .collect::<Vec<String>>()
+        .join("\n\n")
+}
+
+/// A paragraph of the text extracted from an (X)HTML document
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct TextBlock {
+    /// The level of the `h1` to `h6` element holding the text, if any
+    pub heading: Option<u8>,
+    /// The paragraph text, with `br` line breaks kept as `\n`
+    pub text: String,
+}
+
+/// Splits an (X)HTML document into the paragraphs [`html_to_text`] joins,
+/// keeping track of which ones are headings.
+pub(crate) fn html_to_blocks(html: &str) -> Vec<TextBlock> {
+    let mut builder = BlockBuilder::default();
+    let mut skip_depth = 0_usize;
+    let mut rest = html;
+
+    while !rest.is_empty() {
+        let Some(tag_start) = rest.find('<') else {
+            if skip_depth == 0 {
+                builder.push_text(rest);
+            }
+            break;
+        };
+
+        if skip_depth == 0 {
+            builder.push_text(&rest[..tag_start]);
+        }
+
+        rest = &rest[tag_start..];
+
+        // Comments and CDATA sections may hold `>` before their end
+        let tag_end = if rest.starts_with("<!--") {
+            rest.find("-->").map(|end| end + 3)
+        } else if rest.starts_with("<![CDATA[") {
+            rest.find("]]>").map(|end| end + 3)
+        } else {
+            rest.find('>').map(|end| end + 1)
+        };
+        let Some(tag_end) = tag_end else {
+            break;
+        };
+        let tag = &rest[..tag_end];
+
+        rest = &rest[tag_end..];
+
+        if let Some(cdata) = tag
+            .strip_prefix("<![CDATA[")
+            .and_then(|tag| tag.strip_suffix("]]>"))
+        {
+            if skip_depth == 0 {
+                builder.push_text(cdata);
+            }
+            continue;
+        }
+
+        let closing = tag.starts_with("</");
+        let self_closing = tag.ends_with("/>");
+        let name = tag
+            .trim_start_matches(['<', '/'])
+            .split(|c: char| c.is_whitespace() || c == '>' || c == '/')
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+
+        if SKIPPED_ELEMENTS.contains(&name.as_str()) && !self_closing {
+            if closing {
+                skip_depth = skip_depth.saturating_sub(1);
+            } else {
+                skip_depth += 1;
+            }
+        } else if name == "br" {
+            if builder.pending_break.is_empty() {
+                builder.pending_break = "\n";
+            }
+        } else if BLOCK_ELEMENTS.contains(&name.as_str()) {
+            builder.pending_break = "\n\n";
+            builder.pending_heading = match name.strip_prefix('h') {
+                Some(level) if !closing => level.parse().ok(),
+                _ => None,
+            };
+        }
+    }
+
+    builder.blocks
+}
+
+/// Collects the paragraphs of a document as its character data comes in
+#[derive(Default)]
+struct BlockBuilder {
+    blocks: Vec<TextBlock>,
+    /// The line or paragraph break left by the preceding tags
+    pending_break: &'static str,
+    pending_space: bool,
+    /// The heading level of the next paragraph
+    pending_heading: Option<u8>,
+}
+
+impl BlockBuilder {
+    /// Appends character data, collapsing whitespace and placing the line
+    /// or paragraph break left by the preceding tags.
+    fn push_text(&mut self, data: &str) {
+        let data = decode_entities(data);
+
+        for c in data.chars() {
+            if c.is_whitespace() && c != '\u{a0}' {
+                self.pending_space = true;
+                continue;
+            }
+
+            match self.blocks.last_mut() {
+                Some(block) if self.pending_break != "\n\n" => {
+                    if !self.pending_break.is_empty() {
+                        block.text.push_str(self.pending_break);
+                    } else if self.pending_space {
+                        block.text.push(' ');
+                    }
+
+                    block.text.push(c);
+                }
+                _ => self.blocks.push(TextBlock {
+                    heading: self.pending_heading.take(),
+                    text: c.to_string(),
+                }),
+            }
+
+            self.pending_break = "";
+            self.pending_space = false;
+        }
+    }
+}
+
+/// Decodes the XML entities, `&nbsp;` and numeric character references
+fn decode_entities(data: &str) -> String {
+    let mut decoded = String::with_capacity(data.len());
+    let mut rest = data;
+
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let entity = rest[1..]
+            .find(';')
+            .map(|end| &rest[1..end + 1])
+            .and_then(|name| {
+                let c = match name {
+                    "amp" => Some('&'),
+                    "lt" => Some('<'),
+                    "gt" => Some('>'),
+                    "quot" => Some('"'),
+                    "apos" => Some('\''),
+                    "nbsp" => Some('\u{a0}'),
+                    _ => match name.strip_prefix("#x").or_else(|| name.strip_prefix("#X")) {
+                        Some(hex) => u32::from_str_radix(hex, 16).ok().and_then(char::from_u32),
+                        None => name
+                            .strip_prefix('#')
+                            .and_then(|dec| dec.parse().ok())
+                            .and_then(char::from_u32),
+                    },
+                };
+
+                c.map(|c| (c, name.len() + 2))
+            });
+
+        match entity {
+            Some((c, len)) => {
+                decoded.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    decoded.push_str(rest);
+    decoded
+}
+
+#[cfg(test)]
+mod test {
+    use anyhow::Result;
+
+    use super::{TextCount, html_to_text};
+
+    #[tokio::test]
+    async fn extracts_paragraphs_from_xhtml() -> Result<()> {
+        let xhtml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<html xmlns="http://www.w3.org/1999/xhtml">
+  <head>
+    <title>Chapter 1</title>
+    <style>p { margin: 0; }</style>
+  </head>
+  <body>
+    <h1>Chapter&#160;1</h1>
+    <p>It was a <em>bright</em> cold day in April,
+       and the clocks were striking thirteen.</p>
+    <p>Fish &amp; chips<br/>&#x2014; with salt</p>
+    <!-- <p>not prose</p> -->
+  </body>
+</html>
+"#;
+
+        assert_eq!(
+            html_to_text(xhtml),
+            "Chapter\u{a0}1\n\n\
+             It was a bright cold day in April, and the clocks were striking thirteen.\n\n\
+             Fish & chips\n\u{2014} with salt"
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn counts_words_and_cjk_ideographs() -> Result<()> {
+        assert_eq!(
+            TextCount::of("It's a dark & stormy night."),
+            TextCount {
+                words: 5,
+                characters: 22
+            }
+        );
+        assert_eq!(
+            TextCount::of("你好世界"),
+            TextCount {
+                words: 4,
+                characters: 4
+            }
+        );
+
+        Ok(())
+    }
+}
@@ -3,37 +3,50 @@ use std::io::Cursor;
 use anyhow::Result;
 use xml::{EventReader, reader::XmlEvent};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct DocTitle {
     pub title: String,
 }
 
+/// Collects the `docTitle` text out of the `toc.ncx` events.
+#[derive(Default)]
+pub(super) struct DocTitleParser {
+    in_doc_title: bool,
+    title: Option<String>,
+}
+
+impl DocTitleParser {
+    pub(super) fn feed(&mut self, event: &XmlEvent) {
+        match event {
+            XmlEvent::StartElement { name, .. } if name.local_name == "docTitle" => {
+                self.in_doc_title = true;
+            }
+            XmlEvent::Characters(text) if self.in_doc_title && self.title.is_none() => {
+                self.title = Some(text.clone());
+            }
+            _ => {}
+        }
+    }
+
+    pub(super) fn finish(self) -> DocTitle {
+        DocTitle {
+            title: self.title.unwrap_or_default(),
+        }
+    }
+}
+
 impl TryFrom<Vec<u8>> for DocTitle {
     type Error = anyhow::Error;
 
     fn try_from(bytes: Vec<u8>) -> Result<Self> {
         let cursor = Cursor::new(bytes);
         let xml_reader = EventReader::new(cursor);
-        let mut in_doc_title = false;
-        let mut title = String::new();
+        let mut parser = DocTitleParser::default();
 
         for event in xml_reader.into_iter().flatten() {
-            match event {
-                XmlEvent::StartElement { name, .. } => {
-                    if name.local_name == "docTitle" {
-                        in_doc_title = true;
-                    }
-                }
-                XmlEvent::Characters(text) => {
-                    if in_doc_title {
-                        title = text;
-                        break;
-                    }
-                }
-                _ => {}
-            }
+            parser.feed(&event);
         }
 
-        Ok(Self { title })
+        Ok(parser.finish())
     }
 }
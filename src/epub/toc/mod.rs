@@ -1,31 +1,127 @@
 mod doc_title;
+mod nav_doc;
+mod nav_map;
 mod toc_meta;
 
-use std::fs::File;
+use std::io::{Read, Seek};
 
 use anyhow::{Result, bail};
+use xml::EventReader;
+use xml::escape::{escape_str_attribute, escape_str_pcdata};
 use zip::ZipArchive;
 
 pub use self::doc_title::DocTitle;
+pub use self::nav_map::{NavMap, NavPoint};
 pub use self::toc_meta::TocMeta;
 
+use self::doc_title::DocTitleParser;
+use self::nav_map::NavMapParser;
+use self::toc_meta::TocMetaParser;
+
 /// `toc.ncx` file in an EPUB archive, which contains the table of contents.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct Toc {
     pub meta: TocMeta,
     pub doc_title: DocTitle,
+    pub nav_map: NavMap,
 }
 
 impl Toc {
     /// Parses the `OEBPS/toc.ncx` file and extracts.
     pub fn new(bytes: Vec<u8>) -> Result<Toc> {
-        let meta = TocMeta::try_from(bytes.clone())?;
-        let doc_title = DocTitle::try_from(bytes.clone())?;
+        Self::from_reader(bytes.as_slice())
+    }
+
+    /// Parses the `toc.ncx` file straight from `reader`, e.g. the archive
+    /// entry, in a single pass and without buffering it first.
+    pub fn from_reader<R: Read>(reader: R) -> Result<Toc> {
+        let mut meta = TocMetaParser::default();
+        let mut doc_title = DocTitleParser::default();
+        let mut nav_map = NavMapParser::default();
+
+        for event in EventReader::new(reader).into_iter().flatten() {
+            meta.feed(&event);
+            doc_title.feed(&event);
+            nav_map.feed(&event);
+        }
+
+        Ok(Self {
+            meta: meta.finish(),
+            doc_title: doc_title.finish(),
+            nav_map: nav_map.finish(),
+        })
+    }
+
+    /// Returns the top level entries of the table of contents, each holding
+    /// its nested entries.
+    pub fn nav_points(&self) -> &[NavPoint] {
+        &self.nav_map.nav_points
+    }
+
+    /// Serializes the table of contents into an EPUB2 `toc.ncx` document.
+    ///
+    /// `playOrder` values are assigned following the reading order of the
+    /// nested `navPoint` elements.
+    pub fn to_ncx(&self) -> String {
+        let mut play_order = 0;
+        let mut nav_points = String::new();
 
-        Ok(Self { meta, doc_title })
+        for nav_point in &self.nav_map.nav_points {
+            write_ncx_nav_point(&mut nav_points, nav_point, 2, &mut play_order);
+        }
+
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<ncx xmlns="http://www.daisy.org/z3986/2005/ncx/" version="2005-1">
+  <head>
+    <meta name="dtb:uid" content="{uid}"/>
+    <meta name="dtb:depth" content="{depth}"/>
+  </head>
+  <docTitle>
+    <text>{title}</text>
+  </docTitle>
+  <navMap>
+{nav_points}  </navMap>
+</ncx>
+"#,
+            uid = escape_str_attribute(&self.meta.uid),
+            depth = nav_depth(&self.nav_map.nav_points).max(1),
+            title = escape_str_pcdata(&self.doc_title.title),
+            nav_points = nav_points,
+        )
+    }
+
+    /// Serializes the table of contents into an EPUB3 navigation document
+    /// with a `<nav epub:type="toc">` element.
+    pub fn to_nav_xhtml(&self) -> String {
+        let mut items = String::new();
+
+        for nav_point in &self.nav_map.nav_points {
+            write_nav_xhtml_item(&mut items, nav_point, 4);
+        }
+
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE html>
+<html xmlns="http://www.w3.org/1999/xhtml" xmlns:epub="http://www.idpf.org/2007/ops">
+  <head>
+    <title>{title}</title>
+  </head>
+  <body>
+    <nav epub:type="toc" id="toc">
+      <h1>{title}</h1>
+      <ol>
+{items}      </ol>
+    </nav>
+  </body>
+</html>
+"#,
+            title = escape_str_pcdata(&self.doc_title.title),
+            items = items,
+        )
     }
 
-    pub fn resolve_toc_ncx_file(zip: &mut ZipArchive<File>) -> Result<String> {
+    pub fn resolve_toc_ncx_file<R: Read + Seek>(zip: &mut ZipArchive<R>) -> Result<String> {
         const TOP_LEVEL_TOC_PATH: &str = "toc.ncx";
         const DEFAULT_TOC_PATH: &str = "OEBPS/toc.ncx";
         const ALTERNATIVE_TOC_PATH: &str = "OPS/toc.ncx";
@@ -51,20 +147,128 @@ impl Toc {
     }
 }
 
-// Enable tests when fixtures with Creative Commons license are available
-// #[cfg(test)]
-// mod test {
-//     use super::*;
+fn nav_depth(nav_points: &[NavPoint]) -> usize {
+    nav_points
+        .iter()
+        .map(|nav_point| 1 + nav_depth(&nav_point.children))
+        .max()
+        .unwrap_or(0)
+}
 
-//     const TOC_NCX_BYTES: &[u8] = include_bytes!("../../../fixtures/toc.ncx");
+fn write_ncx_nav_point(out: &mut String, nav_point: &NavPoint, depth: usize, order: &mut usize) {
+    let indent = "  ".repeat(depth);
 
-//     #[tokio::test]
-//     async fn parses_toc_ncx() -> Result<()> {
-//         let toc = Toc::new(TOC_NCX_BYTES.to_vec())?;
+    *order += 1;
+    out.push_str(&format!(
+        "{indent}<navPoint id=\"navPoint-{order}\" playOrder=\"{order}\">\n\
+         {indent}  <navLabel>\n\
+         {indent}    <text>{label}</text>\n\
+         {indent}  </navLabel>\n\
+         {indent}  <content src=\"{src}\"/>\n",
+        label = escape_str_pcdata(&nav_point.label),
+        src = escape_str_attribute(&nav_point.content_src),
+    ));
 
-//         assert_eq!(toc.meta.uid, "9781098166304");
-//         assert_eq!(toc.doc_title.title, "AI Engineering");
+    for child in &nav_point.children {
+        write_ncx_nav_point(out, child, depth + 1, order);
+    }
 
-//         Ok(())
-//     }
-// }
+    out.push_str(&format!("{indent}</navPoint>\n"));
+}
+
+fn write_nav_xhtml_item(out: &mut String, nav_point: &NavPoint, depth: usize) {
+    let indent = "  ".repeat(depth);
+    let anchor = format!(
+        "<a href=\"{}\">{}</a>",
+        escape_str_attribute(&nav_point.content_src),
+        escape_str_pcdata(&nav_point.label)
+    );
+
+    if nav_point.children.is_empty() {
+        out.push_str(&format!("{indent}<li>{anchor}</li>\n"));
+        return;
+    }
+
+    out.push_str(&format!("{indent}<li>{anchor}\n{indent}  <ol>\n"));
+
+    for child in &nav_point.children {
+        write_nav_xhtml_item(out, child, depth + 2);
+    }
+
+    out.push_str(&format!("{indent}  </ol>\n{indent}</li>\n"));
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const TOC_NCX: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<ncx xmlns="http://www.daisy.org/z3986/2005/ncx/" version="2005-1">
+    <head>
+        <meta name="dtb:uid" content="9781718500457"/>
+    </head>
+    <docTitle>
+        <text>The Rust Programming Language</text>
+    </docTitle>
+    <navMap>
+        <navPoint id="ch1" playOrder="1">
+            <navLabel><text>Getting Started</text></navLabel>
+            <content src="Text/ch01.xhtml"/>
+            <navPoint id="ch1-1" playOrder="2">
+                <navLabel><text>Installation &amp; Setup</text></navLabel>
+                <content src="Text/ch01.xhtml#installation"/>
+            </navPoint>
+        </navPoint>
+        <navPoint id="ch2" playOrder="3">
+            <navLabel><text>Guessing Game</text></navLabel>
+            <content src="Text/ch02.xhtml"/>
+        </navPoint>
+    </navMap>
+</ncx>
+"#;
+
+    #[tokio::test]
+    async fn parses_nested_nav_points() -> Result<()> {
+        let toc = Toc::new(TOC_NCX.as_bytes().to_vec())?;
+        let nav_points = toc.nav_points();
+
+        assert_eq!(toc.meta.uid, "9781718500457");
+        assert_eq!(toc.doc_title.title, "The Rust Programming Language");
+        assert_eq!(nav_points.len(), 2);
+        assert_eq!(nav_points[0].label, "Getting Started");
+        assert_eq!(nav_points[0].content_src, "Text/ch01.xhtml");
+        assert_eq!(nav_points[0].play_order, 1);
+        assert_eq!(nav_points[0].children.len(), 1);
+        assert_eq!(nav_points[0].children[0].play_order, 2);
+        assert_eq!(nav_points[1].label, "Guessing Game");
+        assert_eq!(nav_points[1].play_order, 3);
+        assert!(nav_points[1].children.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn round_trips_toc_through_ncx() -> Result<()> {
+        let toc = Toc::new(TOC_NCX.as_bytes().to_vec())?;
+        let reparsed = Toc::new(toc.to_ncx().into_bytes())?;
+
+        assert_eq!(reparsed.meta.uid, "9781718500457");
+        assert_eq!(reparsed.doc_title.title, "The Rust Programming Language");
+        assert_eq!(reparsed.nav_map.nav_points.len(), 2);
+        assert_eq!(reparsed.nav_map.nav_points[0].label, "Getting Started");
+        assert_eq!(
+            reparsed.nav_map.nav_points[0].children[0].label,
+            "Installation & Setup"
+        );
+        assert_eq!(
+            reparsed.nav_map.nav_points[0].children[0].content_src,
+            "Text/ch01.xhtml#installation"
+        );
+        assert!(
+            toc.to_nav_xhtml()
+                .contains(r#"<a href="Text/ch02.xhtml">Guessing Game</a>"#)
+        );
+
+        Ok(())
+    }
+}
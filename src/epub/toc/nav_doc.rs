@@ -0,0 +1,157 @@
+use std::io::Read;
+
+use anyhow::Result;
+use xml::{EventReader, reader::XmlEvent};
+
+use crate::util::href::resolve_relative;
+
+use super::{DocTitle, NavMap, NavPoint, Toc, TocMeta};
+
+impl Toc {
+    /// Parses the `<nav epub:type="toc">` list of an EPUB3 navigation
+    /// document into the same structures read from `toc.ncx`.
+    ///
+    /// Links are resolved against `nav_href`, the manifest `href` of the
+    /// navigation document, so they are relative to the OPF file just like
+    /// the `toc.ncx` ones. The document has no `dtb:uid`, so the returned
+    /// `meta.uid` is empty.
+    pub fn from_nav_xhtml<R: Read>(reader: R, nav_href: &str) -> Result<Toc> {
+        let mut title = String::new();
+        let mut in_title = false;
+        let mut in_toc_nav = false;
+        // Depth of the `a`/`span` heading elements holding the entry label
+        let mut label_depth = 0_usize;
+        let mut nav_points: Vec<NavPoint> = Vec::new();
+        let mut stack: Vec<NavPoint> = Vec::new();
+        let mut play_order = 0;
+
+        for event in EventReader::new(reader).into_iter().flatten() {
+            match event {
+                XmlEvent::StartElement {
+                    name, attributes, ..
+                } => match name.local_name.as_str() {
+                    "title" if !in_toc_nav => in_title = true,
+                    "nav" => {
+                        in_toc_nav = attributes.iter().any(|attr| {
+                            attr.name.local_name == "type"
+                                && attr.value.split_whitespace().any(|value| value == "toc")
+                        });
+                    }
+                    "li" if in_toc_nav => {
+                        play_order += 1;
+                        stack.push(NavPoint {
+                            play_order,
+                            ..NavPoint::default()
+                        });
+                    }
+                    "a" | "span" if in_toc_nav => {
+                        label_depth += 1;
+
+                        if let Some(nav_point) = stack.last_mut()
+                            && let Some(href) = attributes
+                                .iter()
+                                .find(|attr| attr.name.local_name == "href")
+                        {
+                            nav_point.content_src = resolve_relative(nav_href, &href.value);
+                        }
+                    }
+                    _ => {}
+                },
+                XmlEvent::Characters(text) | XmlEvent::CData(text) => {
+                    if in_title {
+                        title.push_str(&text);
+                    } else if label_depth > 0
+                        && let Some(nav_point) = stack.last_mut()
+                    {
+                        nav_point.label.push_str(&text);
+                    }
+                }
+                XmlEvent::EndElement { name } => match name.local_name.as_str() {
+                    "title" => in_title = false,
+                    "nav" => in_toc_nav = false,
+                    "a" | "span" if in_toc_nav => label_depth = label_depth.saturating_sub(1),
+                    "li" if in_toc_nav => {
+                        if let Some(mut nav_point) = stack.pop() {
+                            nav_point.label = nav_point.label.trim().to_string();
+
+                            match stack.last_mut() {
+                                Some(parent) => parent.children.push(nav_point),
+                                None => nav_points.push(nav_point),
+                            }
+                        }
+                    }
+                    _ => {}
+                },
+                _ => {}
+            }
+        }
+
+        Ok(Toc {
+            meta: TocMeta { uid: String::new() },
+            doc_title: DocTitle {
+                title: title.trim().to_string(),
+            },
+            nav_map: NavMap { nav_points },
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use anyhow::Result;
+
+    use crate::epub::Toc;
+
+    const NAV_XHTML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE html>
+<html xmlns="http://www.w3.org/1999/xhtml" xmlns:epub="http://www.idpf.org/2007/ops">
+  <head>
+    <title>Dune</title>
+  </head>
+  <body>
+    <nav epub:type="toc" id="toc">
+      <h1>Contents</h1>
+      <ol>
+        <li><a href="../Text/book1.xhtml">Book One: <em>Dune</em></a>
+          <ol>
+            <li><a href="../Text/book1.xhtml#ch1">Chapter 1</a></li>
+          </ol>
+        </li>
+        <li><span>Appendices</span>
+          <ol>
+            <li><a href="../Text/appendix.xhtml">Ecology of Dune</a></li>
+          </ol>
+        </li>
+      </ol>
+    </nav>
+    <nav epub:type="landmarks">
+      <ol>
+        <li><a epub:type="cover" href="../Text/cover.xhtml">Cover</a></li>
+      </ol>
+    </nav>
+  </body>
+</html>
+"#;
+
+    #[tokio::test]
+    async fn parses_epub3_navigation_document() -> Result<()> {
+        let toc = Toc::from_nav_xhtml(NAV_XHTML.as_bytes(), "Nav/nav.xhtml")?;
+        let nav_points = &toc.nav_map.nav_points;
+
+        assert_eq!(toc.doc_title.title, "Dune");
+        assert_eq!(nav_points.len(), 2);
+        assert_eq!(nav_points[0].label, "Book One: Dune");
+        assert_eq!(nav_points[0].content_src, "Text/book1.xhtml");
+        assert_eq!(nav_points[0].children[0].label, "Chapter 1");
+        assert_eq!(
+            nav_points[0].children[0].content_src,
+            "Text/book1.xhtml#ch1"
+        );
+        assert_eq!(nav_points[0].children[0].play_order, 2);
+        assert_eq!(nav_points[1].label, "Appendices");
+        assert_eq!(nav_points[1].content_src, "");
+        assert_eq!(nav_points[1].children[0].content_src, "Text/appendix.xhtml");
+
+        Ok(())
+    }
+}
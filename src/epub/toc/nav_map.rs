@@ -0,0 +1,113 @@
+use std::io::Cursor;
+
+use anyhow::Result;
+use xml::{EventReader, reader::XmlEvent};
+
+/// The `navPoint` element in the `toc.ncx` file, which may nest other
+/// `navPoint` elements for sections within a chapter.
+#[derive(Debug, Clone, Default)]
+pub struct NavPoint {
+    /// The text of the `navLabel` element
+    pub label: String,
+    /// The `src` attribute of the `content` element
+    pub content_src: String,
+    /// The `playOrder` attribute, the position of the entry in reading order
+    pub play_order: usize,
+    pub children: Vec<NavPoint>,
+}
+
+/// The `navMap` element in the `toc.ncx` file
+#[derive(Debug, Clone, Default)]
+pub struct NavMap {
+    pub nav_points: Vec<NavPoint>,
+}
+
+/// Builds the `navPoint` tree out of the `toc.ncx` events, ignoring
+/// anything past the end of the `navMap` element.
+#[derive(Default)]
+pub(super) struct NavMapParser {
+    nav_points: Vec<NavPoint>,
+    stack: Vec<NavPoint>,
+    in_nav_label: bool,
+    done: bool,
+    /// Number of `navPoint` elements started so far, used as the play order
+    /// when the attribute is missing
+    started: usize,
+}
+
+impl NavMapParser {
+    pub(super) fn feed(&mut self, event: &XmlEvent) {
+        if self.done {
+            return;
+        }
+
+        match event {
+            XmlEvent::StartElement {
+                name, attributes, ..
+            } => match name.local_name.as_str() {
+                "navPoint" => {
+                    self.started += 1;
+                    self.stack.push(NavPoint {
+                        play_order: attributes
+                            .iter()
+                            .find(|attr| attr.name.local_name == "playOrder")
+                            .and_then(|attr| attr.value.trim().parse().ok())
+                            .unwrap_or(self.started),
+                        ..NavPoint::default()
+                    });
+                }
+                "navLabel" => self.in_nav_label = true,
+                "content" => {
+                    if let Some(nav_point) = self.stack.last_mut()
+                        && let Some(src) =
+                            attributes.iter().find(|attr| attr.name.local_name == "src")
+                    {
+                        nav_point.content_src = src.value.clone();
+                    }
+                }
+                _ => {}
+            },
+            XmlEvent::Characters(text) if self.in_nav_label => {
+                if let Some(nav_point) = self.stack.last_mut() {
+                    nav_point.label.push_str(text);
+                }
+            }
+            XmlEvent::EndElement { name } => match name.local_name.as_str() {
+                "navLabel" => self.in_nav_label = false,
+                "navPoint" => {
+                    if let Some(nav_point) = self.stack.pop() {
+                        match self.stack.last_mut() {
+                            Some(parent) => parent.children.push(nav_point),
+                            None => self.nav_points.push(nav_point),
+                        }
+                    }
+                }
+                "navMap" => self.done = true,
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+
+    pub(super) fn finish(self) -> NavMap {
+        NavMap {
+            nav_points: self.nav_points,
+        }
+    }
+}
+
+impl TryFrom<Vec<u8>> for NavMap {
+    type Error = anyhow::Error;
+
+    fn try_from(bytes: Vec<u8>) -> Result<Self> {
+        let cursor = Cursor::new(bytes);
+        let xml_reader = EventReader::new(cursor);
+        let mut parser = NavMapParser::default();
+
+        for event in xml_reader.into_iter().flatten() {
+            parser.feed(&event);
+        }
+
+        Ok(parser.finish())
+    }
+}
@@ -3,49 +3,57 @@ use std::io::Cursor;
 use anyhow::Result;
 use xml::{EventReader, reader::XmlEvent};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct TocMeta {
     /// The`dtb:uid` element
     pub uid: String,
 }
 
+/// Collects the `dtb:uid` meta out of the `toc.ncx` events.
+#[derive(Default)]
+pub(super) struct TocMetaParser {
+    uid: String,
+}
+
+impl TocMetaParser {
+    pub(super) fn feed(&mut self, event: &XmlEvent) {
+        if let XmlEvent::StartElement {
+            name, attributes, ..
+        } = event
+            && name.local_name == "meta"
+        {
+            let name_attr = attributes
+                .iter()
+                .find(|attr| attr.name.local_name == "name");
+            let content_attr = attributes
+                .iter()
+                .find(|attr| attr.name.local_name == "content");
+
+            if let (Some(name), Some(content)) = (name_attr, content_attr)
+                && name.value == "dtb:uid"
+            {
+                self.uid = content.value.clone();
+            }
+        }
+    }
+
+    pub(super) fn finish(self) -> TocMeta {
+        TocMeta { uid: self.uid }
+    }
+}
+
 impl TryFrom<Vec<u8>> for TocMeta {
     type Error = anyhow::Error;
 
     fn try_from(bytes: Vec<u8>) -> Result<Self> {
         let cursor = Cursor::new(bytes);
         let xml_reader = EventReader::new(cursor);
-        let mut uid = String::new();
+        let mut parser = TocMetaParser::default();
 
         for event in xml_reader.into_iter().flatten() {
-            match event {
-                XmlEvent::StartElement {
-                    name, attributes, ..
-                } => {
-                    if name.local_name == "meta" {
-                        let name_attr = attributes
-                            .iter()
-                            .find(|attr| attr.name.local_name == "name");
-                        let content_attr = attributes
-                            .iter()
-                            .find(|attr| attr.name.local_name == "content");
-
-                        if let (Some(name), Some(content)) = (name_attr, content_attr)
-                            && name.value == "dtb:uid"
-                        {
-                            uid = content.value.clone();
-                        }
-                    }
-                }
-                XmlEvent::EndElement { name } => {
-                    if name.local_name == "ncx" {
-                        break; // End of the toc.ncx file
-                    }
-                }
-                _ => {}
-            }
+            parser.feed(&event);
         }
 
-        Ok(TocMeta { uid })
+        Ok(parser.finish())
     }
 }
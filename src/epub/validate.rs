@@ -0,0 +1,157 @@
+use std::fmt;
+
+use anyhow::Result;
+
+use crate::epub::container::OEBPS_PACKAGE_MEDIA_TYPE;
+use crate::epub::{DirectionMismatch, Epub, ManifestFinding, MimetypeFinding};
+
+/// A structural problem found in an EPUB archive
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationIssue {
+    Mimetype(MimetypeFinding),
+    /// `META-INF/container.xml` declares no OPF root file
+    MissingRootfile,
+    /// The OPF root file declared by `META-INF/container.xml` is not in the
+    /// archive
+    RootfileNotFound(String),
+    Manifest(ManifestFinding),
+    /// A spine `idref` doesn't match any manifest item
+    UnresolvedSpineItem(String),
+    /// A manifest item points at a file missing from the archive
+    MissingResource {
+        id: String,
+        href: String,
+    },
+    DirectionMismatch(DirectionMismatch),
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Mimetype(finding) => write!(f, "{}", finding),
+            Self::MissingRootfile => write!(f, "The container declares no OPF root file"),
+            Self::RootfileNotFound(path) => {
+                write!(
+                    f,
+                    "The container root file '{}' is not in the archive",
+                    path
+                )
+            }
+            Self::Manifest(finding) => write!(f, "{}", finding),
+            Self::UnresolvedSpineItem(idref) => {
+                write!(f, "Spine item '{}' is not declared in the manifest", idref)
+            }
+            Self::MissingResource { id, href } => write!(
+                f,
+                "Manifest item '{}' points at '{}', which is not in the archive",
+                id, href
+            ),
+            Self::DirectionMismatch(mismatch) => write!(f, "{}", mismatch),
+        }
+    }
+}
+
+impl Epub {
+    /// Checks the structural integrity of the archive, collecting every issue
+    /// found instead of failing on the first one:
+    ///
+    /// - The `mimetype` entry is first, stored and declares an EPUB
+    /// - `META-INF/container.xml` points at an OPF file in the archive
+    /// - Manifest ids and hrefs are unique
+    /// - Every spine `idref` resolves to a manifest item
+    /// - Every manifest `href` exists in the archive
+    /// - The spine page progression direction matches the script of the text
+    pub async fn validate(&self) -> Result<Vec<ValidationIssue>> {
+        let mut issues = self
+            .mimetype_findings()
+            .await?
+            .into_iter()
+            .map(ValidationIssue::Mimetype)
+            .collect::<Vec<ValidationIssue>>();
+        let direction_mismatch = self.direction_mismatch().await?;
+        let content_opf = self.content_opf().await?;
+        let archive = self.archive.lock().await;
+        let exists = |path: &str| archive.index_for_name(path).is_some();
+
+        match self
+            .mic
+            .rootfiles_by_media_type(OEBPS_PACKAGE_MEDIA_TYPE)
+            .next()
+        {
+            Some(rootfile) => {
+                let path = rootfile.full_path.to_string_lossy();
+
+                if !exists(&path) {
+                    issues.push(ValidationIssue::RootfileNotFound(path.to_string()));
+                }
+            }
+            None => issues.push(ValidationIssue::MissingRootfile),
+        }
+
+        issues.extend(
+            content_opf
+                .manifest_findings()
+                .into_iter()
+                .map(ValidationIssue::Manifest),
+        );
+
+        for spine_item in &content_opf.spine {
+            if content_opf.manifest_item(&spine_item.idref).is_none() {
+                issues.push(ValidationIssue::UnresolvedSpineItem(
+                    spine_item.idref.clone(),
+                ));
+            }
+        }
+
+        for item in &content_opf.manifest {
+            // Remote resources, allowed for audio and video, aren't archived
+            if item.href.contains("://") {
+                continue;
+            }
+
+            if !exists(&self.resolve_href(&item.href)) {
+                issues.push(ValidationIssue::MissingResource {
+                    id: item.id.clone(),
+                    href: item.href.clone(),
+                });
+            }
+        }
+
+        issues.extend(direction_mismatch.map(ValidationIssue::DirectionMismatch));
+
+        Ok(issues)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use anyhow::Result;
+
+    use super::ValidationIssue;
+    use crate::epub::test_util::{EpubBuilder, content_opf};
+
+    #[tokio::test]
+    async fn reads_the_percent_encoded_hrefs_it_validates() -> Result<()> {
+        let epub = EpubBuilder::new(&content_opf(
+            "<dc:identifier id=\"BookId\">urn:uuid:1234</dc:identifier>",
+            &[("ch1", "Text/My%20Chapter.xhtml", "application/xhtml+xml")],
+        ))
+        .entry(
+            "Text/My Chapter.xhtml",
+            "<html><body><p>Found</p></body></html>",
+        )
+        .open()?;
+
+        assert!(
+            !epub
+                .validate()
+                .await?
+                .iter()
+                .any(|issue| matches!(issue, ValidationIssue::MissingResource { .. }))
+        );
+        assert_eq!(epub.text().await?, "Found");
+        assert_eq!(epub.asset_summary().await?[0].count, 1);
+
+        Ok(())
+    }
+}
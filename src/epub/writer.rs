@@ -1,9 +1,18 @@
-use std::fs::File;
-use std::io::Write;
+use std::fs::{File, read_dir};
+use std::io::{Write, copy};
 use std::path::{Path, PathBuf};
 
 use anyhow::{Result, bail};
 use zip::write::{ExtendedFileOptions, FileOptions, ZipWriter};
+use zip::{CompressionMethod, DateTime};
+
+use crate::epub::container::CONTAINER_XML;
+
+const META_INF: &str = "META-INF";
+const MIMETYPE: &str = "mimetype";
+/// Deflate level used for every compressed entry, so the output doesn't
+/// depend on the defaults of the `zip` crate.
+const COMPRESSION_LEVEL: i64 = 6;
 
 pub struct EpubWriter {
     source: PathBuf,
@@ -22,50 +31,399 @@ impl EpubWriter {
         Ok(EpubWriter { source, zip_writer })
     }
 
-    pub async fn write(&mut self) -> Result<()> {
+    /// Repackages a directory produced by [`Epub::unpackage`] into `output`,
+    /// keeping its directory structure, with the `mimetype` file Stored and
+    /// every other file Deflated, so an untouched round trip yields a valid
+    /// EPUB.
+    ///
+    /// [`Epub::unpackage`]: crate::epub::Epub::unpackage
+    pub async fn repack<P: AsRef<Path>, Q: AsRef<Path>>(source: P, output: Q) -> Result<()> {
+        let source = source.as_ref();
+
+        if !source.join(CONTAINER_XML).is_file() {
+            bail!(
+                "The source '{:?}' is not an unpackaged EPUB, {} is missing",
+                source,
+                CONTAINER_XML
+            )
+        }
+
+        Self::new(File::create(output)?, source)?.write().await
+    }
+
+    /// Packages the source directory: the `mimetype` file first, then the
+    /// `META-INF` directory and every other file, keeping their paths
+    /// relative to the source directory. Empty directories are kept as
+    /// directory entries.
+    ///
+    /// The output is reproducible: files within `META-INF` and within the
+    /// rest of the book are written sorted by their archive path, every
+    /// entry gets the fixed 1980-01-01 00:00:00 timestamp and a fixed
+    /// compression level, so packaging the same directory twice yields
+    /// byte-identical archives.
+    pub async fn write(mut self) -> Result<()> {
         self.write_mimetype()?;
         self.write_meta_inf().await?;
+        self.write_contents()?;
+        self.zip_writer.finish()?;
         Ok(())
     }
 
     /// Writes the `mimetype` file as the first file in the EPUB archive.
     fn write_mimetype(&mut self) -> Result<()> {
-        let options: FileOptions<'_, ExtendedFileOptions> =
-            FileOptions::default().compression_method(zip::CompressionMethod::Stored);
-        self.zip_writer.start_file("mimetype", options)?;
+        self.zip_writer
+            .start_file(MIMETYPE, file_options(CompressionMethod::Stored))?;
         self.zip_writer.write_all(b"application/epub+zip")?;
         Ok(())
     }
 
     /// Copies the contents of the META-INF directory into the EPUB archive.
     async fn write_meta_inf(&mut self) -> Result<()> {
-        let path = self.source.join("META-INF");
+        let path = self.source.join(META_INF);
         let file = File::open(&path)?;
 
         if !file.metadata()?.is_dir() {
             bail!("The source '{:?}' is not a directory", path)
         }
 
-        let options: FileOptions<'_, ExtendedFileOptions> =
-            FileOptions::default().compression_method(zip::CompressionMethod::Stored);
+        let mut files = Vec::new();
+        collect_files(&self.source, &path, &mut files)?;
+
+        self.write_files(files)
+    }
+
+    /// Copies every other file in the source directory into the EPUB archive.
+    /// The `mimetype` file and the `META-INF` directory at the top
+    /// level are skipped, as they're already written.
+    fn write_contents(&mut self) -> Result<()> {
+        let mut files = Vec::new();
+        collect_files(&self.source, &self.source, &mut files)?;
+        files.retain(|(name, _)| name != MIMETYPE && !name.starts_with("META-INF/"));
+
+        self.write_files(files)
+    }
+
+    /// Writes `files` Deflated, sorted by their archive path. Contents are
+    /// streamed from disk, so large media files aren't held in memory.
+    fn write_files(&mut self, mut files: Vec<(String, PathBuf)>) -> Result<()> {
+        files.sort();
+
+        for (name, file_path) in files {
+            if name.ends_with('/') {
+                self.zip_writer
+                    .add_directory(name, file_options(CompressionMethod::Stored))?;
+                continue;
+            }
 
-        for entry in std::fs::read_dir(&path)? {
-            let entry = entry?;
-            let file_path = entry.path();
-            let file_name = file_path
-                .file_name()
-                .and_then(|name| name.to_str())
-                .ok_or_else(|| anyhow::anyhow!("Failed to get file name"))?;
             let mut file = File::open(&file_path)?;
-            let mut buffer = Vec::new();
-            use std::io::Read;
-            file.read_to_end(&mut buffer)?;
+            // Entries of 4 GiB and beyond need the ZIP64 extensions
+            let large_file = file.metadata()?.len() >= u64::from(u32::MAX);
+
+            self.zip_writer.start_file(
+                name,
+                file_options(CompressionMethod::Deflated).large_file(large_file),
+            )?;
+            copy(&mut file, &mut self.zip_writer)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Options for an archive entry with a fixed timestamp and compression
+/// level, so the output doesn't vary between runs.
+fn file_options(
+    compression_method: CompressionMethod,
+) -> FileOptions<'static, ExtendedFileOptions> {
+    let compression_level = match compression_method {
+        CompressionMethod::Stored => None,
+        _ => Some(COMPRESSION_LEVEL),
+    };
 
-            self.zip_writer
-                .start_file(format!("META-INF/{}", file_name), options.clone())?;
-            self.zip_writer.write_all(&buffer)?;
+    FileOptions::default()
+        .compression_method(compression_method)
+        .compression_level(compression_level)
+        .last_modified_time(DateTime::DEFAULT)
+}
+
+/// Recursively collects the files under `dir` as pairs of their archive
+/// path, relative to `source` and joined with `/`, and their path on disk.
+/// Empty directories are collected too, with a trailing `/` in their name.
+fn collect_files(source: &Path, dir: &Path, files: &mut Vec<(String, PathBuf)>) -> Result<()> {
+    let mut is_empty = true;
+
+    for entry in read_dir(dir)? {
+        let file_path = entry?.path();
+        is_empty = false;
+
+        if file_path.is_dir() {
+            collect_files(source, &file_path, files)?;
+            continue;
         }
 
+        files.push((archive_name(source, &file_path)?, file_path));
+    }
+
+    if is_empty && dir != source {
+        files.push((
+            format!("{}/", archive_name(source, dir)?),
+            dir.to_path_buf(),
+        ));
+    }
+
+    Ok(())
+}
+
+fn archive_name(source: &Path, path: &Path) -> Result<String> {
+    Ok(path
+        .strip_prefix(source)?
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/"))
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs::{File, create_dir_all, read, read_to_string, remove_dir_all, write};
+    use std::io::{Read, copy, repeat};
+    use std::path::Path;
+
+    use anyhow::Result;
+    use sha1::{Digest, Sha1};
+    use zip::{CompressionMethod, ZipArchive};
+
+    use crate::epub::{Epub, EpubWriter};
+
+    fn entries(path: &Path) -> Result<Vec<(String, CompressionMethod)>> {
+        let mut archive = ZipArchive::new(File::open(path)?)?;
+        let mut entries = Vec::new();
+
+        for index in 0..archive.len() {
+            let file = archive.by_index(index)?;
+            entries.push((file.name().to_string(), file.compression()));
+        }
+
+        Ok(entries)
+    }
+
+    fn write_book(dir: &Path) -> Result<()> {
+        create_dir_all(dir.join("META-INF"))?;
+        create_dir_all(dir.join("OEBPS/Text"))?;
+        write(dir.join("mimetype"), "application/epub+zip")?;
+        write(
+            dir.join("META-INF/container.xml"),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+    <rootfiles>
+        <rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/>
+    </rootfiles>
+</container>
+"#,
+        )?;
+        write(
+            dir.join("OEBPS/content.opf"),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="2.0" unique-identifier="BookId">
+    <metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+        <dc:title>Round Trip</dc:title>
+        <dc:identifier id="BookId">9780306406157</dc:identifier>
+    </metadata>
+    <manifest>
+        <item id="ncx" href="toc.ncx" media-type="application/x-dtbncx+xml"/>
+        <item id="ch1" href="Text/ch1.xhtml" media-type="application/xhtml+xml"/>
+    </manifest>
+    <spine toc="ncx">
+        <itemref idref="ch1"/>
+    </spine>
+</package>
+"#,
+        )?;
+        write(
+            dir.join("OEBPS/toc.ncx"),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<ncx xmlns="http://www.daisy.org/z3986/2005/ncx/" version="2005-1">
+    <head><meta name="dtb:uid" content="9780306406157"/></head>
+    <docTitle><text>Round Trip</text></docTitle>
+    <navMap>
+        <navPoint id="ch1" playOrder="1">
+            <navLabel><text>Chapter 1</text></navLabel>
+            <content src="Text/ch1.xhtml"/>
+        </navPoint>
+    </navMap>
+</ncx>
+"#,
+        )?;
+        write(
+            dir.join("OEBPS/Text/ch1.xhtml"),
+            r#"<html xmlns="http://www.w3.org/1999/xhtml"><body><p>Hello</p></body></html>"#,
+        )?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn repackages_unpackaged_epub() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("bookworm-writer-{}", std::process::id()));
+        let source = dir.join("source");
+        let unpackaged = dir.join("unpackaged");
+
+        write_book(&source)?;
+        EpubWriter::new(File::create(dir.join("first.epub"))?, &source)?
+            .write()
+            .await?;
+        Epub::unpackage(dir.join("first.epub"), unpackaged.clone())?;
+        EpubWriter::new(File::create(dir.join("second.epub"))?, &unpackaged)?
+            .write()
+            .await?;
+
+        let epub = Epub::open(dir.join("second.epub"))?;
+        let entries = epub.list_entries().await?;
+
+        assert_eq!(entries[0], "mimetype");
+        assert_eq!(entries.len(), 5);
+        assert_eq!(epub.content_opf().await?.metadata.title, "Round Trip");
+        assert_eq!(epub.toc().await?.nav_points()[0].label, "Chapter 1");
+        assert_eq!(epub.text().await?, "Hello");
+
+        remove_dir_all(dir)?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn packages_reproducibly() -> Result<()> {
+        let dir =
+            std::env::temp_dir().join(format!("bookworm-reproducible-{}", std::process::id()));
+        let source = dir.join("source");
+
+        write_book(&source)?;
+        write(source.join("OEBPS/Text/ch0.xhtml"), "<html/>")?;
+        write(
+            source.join("META-INF/com.apple.ibooks.display-options.xml"),
+            "<display_options/>",
+        )?;
+
+        for name in ["first.epub", "second.epub"] {
+            EpubWriter::new(File::create(dir.join(name))?, &source)?
+                .write()
+                .await?;
+        }
+
+        assert_eq!(
+            read(dir.join("first.epub"))?,
+            read(dir.join("second.epub"))?
+        );
+
+        let entries = Epub::open(dir.join("first.epub"))?.list_entries().await?;
+
+        assert_eq!(
+            entries,
+            [
+                "mimetype",
+                "META-INF/com.apple.ibooks.display-options.xml",
+                "META-INF/container.xml",
+                "OEBPS/Text/ch0.xhtml",
+                "OEBPS/Text/ch1.xhtml",
+                "OEBPS/content.opf",
+                "OEBPS/toc.ncx",
+            ]
+        );
+
+        remove_dir_all(dir)?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn repack_keeps_structure_and_compression() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("bookworm-repack-{}", std::process::id()));
+        let source = dir.join("source");
+        let unpackaged = dir.join("unpackaged");
+
+        write_book(&source)?;
+        create_dir_all(source.join("OEBPS/Misc"))?;
+        EpubWriter::new(File::create(dir.join("original.epub"))?, &source)?
+            .write()
+            .await?;
+        Epub::unpackage(dir.join("original.epub"), unpackaged.clone())?;
+        EpubWriter::repack(&unpackaged, dir.join("repacked.epub")).await?;
+
+        let original = entries(&dir.join("original.epub"))?;
+        let repacked = entries(&dir.join("repacked.epub"))?;
+
+        assert_eq!(original, repacked);
+        assert!(repacked.contains(&("OEBPS/Misc/".to_string(), CompressionMethod::Stored)));
+        assert!(
+            repacked
+                .iter()
+                .filter(|(name, _)| name != "mimetype" && !name.ends_with('/'))
+                .all(|(_, compression)| *compression == CompressionMethod::Deflated)
+        );
+        assert!(
+            EpubWriter::repack(&dir, dir.join("invalid.epub"))
+                .await
+                .is_err()
+        );
+
+        remove_dir_all(dir)?;
+
+        Ok(())
+    }
+
+    /// Peak resident set size of the process in KiB, where the platform
+    /// reports it
+    fn peak_rss() -> Option<u64> {
+        read_to_string("/proc/self/status")
+            .ok()?
+            .lines()
+            .find_map(|line| line.strip_prefix("VmHWM:"))?
+            .trim()
+            .trim_end_matches("kB")
+            .trim()
+            .parse()
+            .ok()
+    }
+
+    #[tokio::test]
+    async fn streams_large_entries() -> Result<()> {
+        const SIZE: u64 = 32 * 1024 * 1024;
+
+        let dir = std::env::temp_dir().join(format!("bookworm-large-{}", std::process::id()));
+        let source = dir.join("source");
+        let unpackaged = dir.join("unpackaged");
+
+        write_book(&source)?;
+        create_dir_all(source.join("OEBPS/Audio"))?;
+        copy(
+            &mut repeat(b'a').take(SIZE),
+            &mut File::create(source.join("OEBPS/Audio/track.mp3"))?,
+        )?;
+
+        let before = peak_rss();
+        EpubWriter::new(File::create(dir.join("large.epub"))?, &source)?
+            .write()
+            .await?;
+        Epub::unpackage(dir.join("large.epub"), unpackaged.clone())?;
+        let after = peak_rss();
+
+        let mut expected = Sha1::new();
+        copy(&mut repeat(b'a').take(SIZE), &mut expected)?;
+        let mut extracted = Sha1::new();
+        copy(
+            &mut File::open(unpackaged.join("OEBPS/Audio/track.mp3"))?,
+            &mut extracted,
+        )?;
+
+        assert_eq!(extracted.finalize(), expected.finalize());
+
+        // Holding the entry in memory would grow the peak by its whole size
+        if let (Some(before), Some(after)) = (before, after) {
+            assert!(after - before < SIZE / 1024 / 2);
+        }
+
+        remove_dir_all(dir)?;
+
         Ok(())
     }
 }
@@ -1,3 +1,4 @@
+pub mod convert;
 pub mod epub;
 pub mod pdf;
 pub mod util;
@@ -0,0 +1,134 @@
+use std::collections::BTreeMap;
+
+use crate::pdf::Pdf;
+
+/// A difference between the Info dictionaries of two PDF files
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MetadataChange {
+    Added {
+        key: String,
+        value: String,
+    },
+    Removed {
+        key: String,
+        value: String,
+    },
+    Changed {
+        key: String,
+        from: String,
+        to: String,
+    },
+}
+
+/// Result of comparing the metadata of two PDF files
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PdfComparison {
+    /// Info dictionary changes, sorted by key
+    pub changes: Vec<MetadataChange>,
+    pub page_counts: (usize, usize),
+}
+
+impl PdfComparison {
+    /// Returns `true` when both files share the same metadata and page count
+    pub fn is_unchanged(&self) -> bool {
+        self.changes.is_empty() && self.page_counts.0 == self.page_counts.1
+    }
+}
+
+impl Pdf {
+    /// Compares the Info dictionary and page count of this PDF against
+    /// `other`, field by field.
+    pub fn compare(&self, other: &Pdf) -> PdfComparison {
+        let before = self.info_entries();
+        let mut after = other.info_entries();
+        let mut changes = Vec::new();
+
+        for (key, from) in before {
+            match after.remove(&key) {
+                Some(to) if to != from => changes.push(MetadataChange::Changed { key, from, to }),
+                Some(_) => {}
+                None => changes.push(MetadataChange::Removed { key, value: from }),
+            }
+        }
+
+        changes.extend(
+            after
+                .into_iter()
+                .map(|(key, value)| MetadataChange::Added { key, value }),
+        );
+        changes.sort_by(|a, b| change_key(a).cmp(change_key(b)));
+
+        PdfComparison {
+            changes,
+            page_counts: (self.doc.get_pages().len(), other.doc.get_pages().len()),
+        }
+    }
+
+    fn info_entries(&self) -> BTreeMap<String, String> {
+        self.all_metadata().into_iter().collect()
+    }
+}
+
+fn change_key(change: &MetadataChange) -> &str {
+    match change {
+        MetadataChange::Added { key, .. }
+        | MetadataChange::Removed { key, .. }
+        | MetadataChange::Changed { key, .. } => key,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use lopdf::{Document, Object, dictionary};
+
+    use super::*;
+
+    fn pdf_with_info(info: lopdf::Dictionary) -> Pdf {
+        let mut doc = Document::with_version("1.5");
+        let info_id = doc.add_object(info);
+        doc.trailer.set("Info", Object::Reference(info_id));
+
+        Pdf {
+            path: None,
+            doc,
+            metadata_only: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn reports_metadata_drift_between_pdfs() {
+        let a = pdf_with_info(dictionary! {
+            "Title" => Object::string_literal("Draft"),
+            "Author" => Object::string_literal("Jane Doe"),
+            "Producer" => Object::string_literal("Writer"),
+        });
+        let b = pdf_with_info(dictionary! {
+            "Title" => Object::string_literal("Final"),
+            "Author" => Object::string_literal("Jane Doe"),
+            "Subject" => Object::string_literal("Essays"),
+        });
+
+        let comparison = a.compare(&b);
+
+        assert_eq!(
+            comparison.changes,
+            vec![
+                MetadataChange::Removed {
+                    key: "Producer".to_string(),
+                    value: "Writer".to_string(),
+                },
+                MetadataChange::Added {
+                    key: "Subject".to_string(),
+                    value: "Essays".to_string(),
+                },
+                MetadataChange::Changed {
+                    key: "Title".to_string(),
+                    from: "Draft".to_string(),
+                    to: "Final".to_string(),
+                },
+            ]
+        );
+        assert_eq!(comparison.page_counts, (0, 0));
+        assert!(!comparison.is_unchanged());
+    }
+}
@@ -1,20 +1,113 @@
-use std::path::Path;
+mod compare;
+mod xmp;
 
-use anyhow::Result;
-use lopdf::Document;
+pub use compare::{MetadataChange, PdfComparison};
+
+use std::fmt;
+use std::fs::{File, canonicalize};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use anyhow::{Result, bail};
+use chrono::Utc;
+use lopdf::{Dictionary, Document, Object, ObjectId, StringFormat};
+use serde::Serialize;
+
+use crate::util::fs::replace_with;
 
 const PDF_META_INFO_KEY: &[u8] = b"Info";
 const PDF_META_TITLE_KEY: &[u8] = b"Title";
 const PDF_META_AUTHOR_KEY: &[u8] = b"Author";
+const PDF_META_SUBJECT_KEY: &[u8] = b"Subject";
+const PDF_META_KEYWORDS_KEY: &[u8] = b"Keywords";
 const PDF_META_CREATOR_KEY: &[u8] = b"Creator";
 const PDF_META_PRODUCER_KEY: &[u8] = b"Producer";
 const PDF_META_CREATION_DATE_KEY: &[u8] = b"CreationDate";
 const PDF_META_MODIFICATION_DATE_KEY: &[u8] = b"ModDate";
+const PDF_TRAILER_ENCRYPT_KEY: &[u8] = b"Encrypt";
+const PDF_CATALOG_LANG_KEY: &[u8] = b"Lang";
+const PDF_CATALOG_METADATA_KEY: &[u8] = b"Metadata";
 
-#[derive(Debug)]
+/// A well-known entry of the PDF Info dictionary
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PdfMetaField {
+    Title,
+    Author,
+    Subject,
+    Keywords,
+    Creator,
+    Producer,
+    CreationDate,
+    ModificationDate,
+}
+
+impl PdfMetaField {
+    /// Every known field, in the order they are reported
+    pub const ALL: [PdfMetaField; 8] = [
+        Self::Title,
+        Self::Author,
+        Self::Subject,
+        Self::Keywords,
+        Self::Creator,
+        Self::Producer,
+        Self::CreationDate,
+        Self::ModificationDate,
+    ];
+
+    /// Returns the Info dictionary key for this field
+    pub fn as_bytes(&self) -> &'static [u8] {
+        match self {
+            Self::Title => PDF_META_TITLE_KEY,
+            Self::Author => PDF_META_AUTHOR_KEY,
+            Self::Subject => PDF_META_SUBJECT_KEY,
+            Self::Keywords => PDF_META_KEYWORDS_KEY,
+            Self::Creator => PDF_META_CREATOR_KEY,
+            Self::Producer => PDF_META_PRODUCER_KEY,
+            Self::CreationDate => PDF_META_CREATION_DATE_KEY,
+            Self::ModificationDate => PDF_META_MODIFICATION_DATE_KEY,
+        }
+    }
+}
+
+impl fmt::Display for PdfMetaField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", String::from_utf8_lossy(self.as_bytes()))
+    }
+}
+
+impl FromStr for PdfMetaField {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "title" => Ok(Self::Title),
+            "author" => Ok(Self::Author),
+            "subject" => Ok(Self::Subject),
+            "keywords" => Ok(Self::Keywords),
+            "creator" => Ok(Self::Creator),
+            "producer" => Ok(Self::Producer),
+            "creationdate" | "creation_date" => Ok(Self::CreationDate),
+            "moddate" | "modification_date" => Ok(Self::ModificationDate),
+            _ => bail!("Unknown PDF metadata field '{}'", s),
+        }
+    }
+}
+
+/// Options for [`Pdf::save_with`]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SaveOptions {
+    /// Stamps the `ModDate` Info entry with the current time before writing
+    pub update_mod_date: bool,
+}
+
+#[derive(Debug, Serialize)]
 pub struct PdfMetadata {
     pub title: Option<String>,
     pub author: Option<String>,
+    pub subject: Option<String>,
+    /// The raw `Keywords` entry, usually a comma separated list
+    pub keywords: Option<String>,
     pub creator: Option<String>,
     pub producer: Option<String>,
     pub creation_date: Option<String>,
@@ -23,19 +116,200 @@ pub struct PdfMetadata {
 
 #[derive(Debug)]
 pub struct Pdf {
+    /// The file this document was opened from, if any
+    path: Option<PathBuf>,
     doc: Document,
+    /// Whether the document was loaded without its content streams
+    metadata_only: bool,
 }
 
 impl Pdf {
     pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
-        let doc = Document::load(path)?;
-        Ok(Pdf { doc })
+        let path = path.as_ref().to_path_buf();
+        let mut pdf = Self::from_reader(File::open(&path)?)?;
+
+        pdf.path = Some(path);
+        Ok(pdf)
+    }
+
+    /// Opens a password protected PDF, decrypting it with its user password.
+    /// Saving the document writes it unencrypted.
+    pub fn open_with_password<P: AsRef<Path>>(path: P, password: &str) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let doc = match Document::load_with_password(&path, password) {
+            Ok(doc) => doc,
+            Err(lopdf::Error::InvalidPassword) => bail!(
+                "Cannot decrypt '{}', the password is incorrect",
+                path.display()
+            ),
+            Err(err) => return Err(err.into()),
+        };
+
+        if !doc.was_encrypted() && !doc.is_encrypted() {
+            bail!(
+                "Cannot decrypt '{}', the PDF is not encrypted",
+                path.display()
+            );
+        }
+
+        if doc.is_encrypted() {
+            bail!(
+                "Cannot decrypt '{}', the password is incorrect",
+                path.display()
+            );
+        }
+
+        Ok(Pdf {
+            path: Some(path),
+            doc,
+            metadata_only: false,
+        })
+    }
+
+    /// Reads the PDF from any byte source, e.g. an HTTP response body or an
+    /// in-memory buffer, without going through a file.
+    pub fn from_reader<R: Read>(reader: R) -> Result<Self> {
+        let doc = Document::load_from(reader)?;
+        Ok(Pdf {
+            path: None,
+            doc,
+            metadata_only: false,
+        })
+    }
+
+    /// Opens the PDF skipping page contents, images and any other stream not
+    /// needed to read the document metadata, which keeps memory use low when
+    /// listing metadata over large collections. The whole file is still
+    /// parsed, so it isn't much faster to open.
+    ///
+    /// Text extraction is unavailable on these documents and they can't be
+    /// saved. Encrypted documents are loaded in full.
+    pub fn open_metadata_only<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let doc = Document::load_filtered(&path, keep_metadata_object)?;
+        Ok(Pdf {
+            path: Some(path),
+            doc,
+            metadata_only: true,
+        })
+    }
+
+    /// Writes the document into `path` as is. Overwriting the file this
+    /// document was opened from is not allowed.
+    pub fn save<P: AsRef<Path>>(&mut self, path: P) -> Result<()> {
+        self.save_with(path, SaveOptions::default())
+    }
+
+    /// Writes the document into `path` applying the provided [`SaveOptions`].
+    /// Overwriting the file this document was opened from is not allowed.
+    pub fn save_with<P: AsRef<Path>>(&mut self, path: P, options: SaveOptions) -> Result<()> {
+        let path = path.as_ref();
+
+        if let Some(source) = &self.path
+            && path.exists()
+            && canonicalize(path)? == canonicalize(source)?
+        {
+            bail!("Cannot overwrite source file '{}'", source.display());
+        }
+
+        self.write(path, options)
+    }
+
+    /// Overwrites the file this document was opened from. The document is
+    /// written into a temporary file next to it, which is then renamed over
+    /// the original so it is never left half written.
+    pub fn save_in_place(&mut self) -> Result<()> {
+        self.save_in_place_with(SaveOptions::default())
+    }
+
+    /// Overwrites the file this document was opened from applying the
+    /// provided [`SaveOptions`]. See [`Pdf::save_in_place`].
+    pub fn save_in_place_with(&mut self, options: SaveOptions) -> Result<()> {
+        let Some(source) = self.path.clone() else {
+            bail!("Cannot save in place a PDF not opened from a file");
+        };
+
+        replace_with(&source, |temp_path| self.write(temp_path, options))
+    }
+
+    fn write(&mut self, path: &Path, options: SaveOptions) -> Result<()> {
+        if self.metadata_only {
+            bail!("Cannot save a PDF opened for metadata only");
+        }
+
+        if options.update_mod_date {
+            info_dict_mut(&mut self.doc)?.set(PDF_META_MODIFICATION_DATE_KEY, Utc::now());
+        }
+
+        self.doc.save(path)?;
+        Ok(())
+    }
+
+    /// Returns whether the document was encrypted, whether it could be
+    /// decrypted or not.
+    pub fn is_encrypted(&self) -> bool {
+        self.doc.was_encrypted() || self.is_locked()
+    }
+
+    /// Returns whether the document is encrypted and couldn't be decrypted,
+    /// in which case its strings and streams are unreadable. Documents with
+    /// an empty user password are decrypted on load, which drops the trailer
+    /// `/Encrypt` entry.
+    pub fn is_locked(&self) -> bool {
+        self.doc.trailer.has(PDF_TRAILER_ENCRYPT_KEY)
+    }
+
+    /// Returns the number of pages in the document
+    pub fn page_count(&self) -> usize {
+        self.doc.get_pages().len()
+    }
+
+    /// Extracts the text of every page in order, yielding each page as it is
+    /// extracted along with its 1-based page number.
+    pub fn pages_text(&self) -> impl Iterator<Item = (u32, Result<String>)> + '_ {
+        self.doc.get_pages().into_keys().map(|page_number| {
+            let text = self
+                .doc
+                .extract_text(&[page_number])
+                .map_err(anyhow::Error::from);
+
+            (page_number, text)
+        })
+    }
+
+    /// Returns the document language declared by the `/Lang` entry in the
+    /// document catalog.
+    pub fn language(&self) -> Option<String> {
+        self.doc
+            .catalog()
+            .ok()?
+            .get(PDF_CATALOG_LANG_KEY)
+            .ok()
+            .and_then(|value| value.as_str().ok())
+            .map(decode_text_string)
+    }
+
+    /// Returns a copy of this document with the catalog `/Lang` entry set to
+    /// `language`.
+    pub fn set_language(&self, language: &str) -> Result<Pdf> {
+        let mut doc = self.doc.clone();
+
+        doc.catalog_mut()?
+            .set(PDF_CATALOG_LANG_KEY, Object::string_literal(language));
+
+        Ok(Pdf {
+            path: self.path.clone(),
+            doc,
+            metadata_only: self.metadata_only,
+        })
     }
 
     pub fn metadata(&self) -> Result<PdfMetadata> {
         Ok(PdfMetadata {
             title: self.get_metadata_field(PDF_META_TITLE_KEY),
             author: self.get_metadata_field(PDF_META_AUTHOR_KEY),
+            subject: self.get_metadata_field(PDF_META_SUBJECT_KEY),
+            keywords: self.get_metadata_field(PDF_META_KEYWORDS_KEY),
             creator: self.get_metadata_field(PDF_META_CREATOR_KEY),
             producer: self.get_metadata_field(PDF_META_PRODUCER_KEY),
             creation_date: self.get_metadata_field(PDF_META_CREATION_DATE_KEY),
@@ -43,11 +317,123 @@ impl Pdf {
         })
     }
 
+    /// Returns every entry of the Info dictionary, including custom keys.
+    ///
+    /// Entries are sorted in a stable order: known fields first, following
+    /// [`PdfMetaField::ALL`], then custom keys sorted alphabetically.
+    pub fn all_metadata(&self) -> Vec<(String, String)> {
+        let Some(dict) = self.info_dict() else {
+            return Vec::new();
+        };
+
+        let mut entries = dict
+            .iter()
+            .map(|(key, value)| (key.as_slice(), object_to_string(value)))
+            .collect::<Vec<(&[u8], String)>>();
+
+        entries.sort_by_key(|(key, _)| {
+            let position = PdfMetaField::ALL
+                .iter()
+                .position(|field| field.as_bytes() == *key)
+                .unwrap_or(PdfMetaField::ALL.len());
+
+            (position, *key)
+        });
+
+        entries
+            .into_iter()
+            .map(|(key, value)| (String::from_utf8_lossy(key).to_string(), value))
+            .collect()
+    }
+
+    /// Returns a copy of this document with the Info dictionary `field` set
+    /// to `value`. Non-ASCII values are written as UTF-16BE text strings.
+    pub fn set_metadata(&self, field: &PdfMetaField, value: &str) -> Result<Pdf> {
+        let mut doc = self.doc.clone();
+
+        info_dict_mut(&mut doc)?.set(field.as_bytes(), encode_text_string(value));
+
+        Ok(Pdf {
+            path: self.path.clone(),
+            doc,
+            metadata_only: self.metadata_only,
+        })
+    }
+
+    /// Returns a copy of this document without the Info dictionary `field`.
+    /// Documents without the field, or without an Info dictionary, are copied
+    /// as is.
+    pub fn clear_metadata(&self, field: &PdfMetaField) -> Result<Pdf> {
+        let mut doc = self.doc.clone();
+
+        if let Ok(Object::Reference(id)) = doc.trailer.get(PDF_META_INFO_KEY)
+            && let Ok(info) = doc.get_object_mut(*id).and_then(Object::as_dict_mut)
+        {
+            info.remove(field.as_bytes());
+        }
+
+        Ok(Pdf {
+            path: self.path.clone(),
+            doc,
+            metadata_only: self.metadata_only,
+        })
+    }
+
+    /// Returns whether the document catalog references an XMP metadata
+    /// stream.
+    pub fn has_xmp_metadata(&self) -> bool {
+        self.doc
+            .catalog()
+            .is_ok_and(|catalog| catalog.has(PDF_CATALOG_METADATA_KEY))
+    }
+
+    /// Returns a copy of this document without metadata: every Info
+    /// dictionary entry is removed, as well as the XMP metadata stream
+    /// referenced by the document catalog.
+    pub fn strip_all_metadata(&self) -> Result<Pdf> {
+        let mut doc = self.doc.clone();
+
+        if let Ok(Object::Reference(id)) = doc.trailer.get(PDF_META_INFO_KEY)
+            && let Ok(info) = doc.get_object_mut(*id).and_then(Object::as_dict_mut)
+        {
+            *info = Dictionary::new();
+        }
+
+        if let Some(Object::Reference(id)) = doc.catalog_mut()?.remove(PDF_CATALOG_METADATA_KEY) {
+            doc.objects.remove(&id);
+        }
+
+        Ok(Pdf {
+            path: self.path.clone(),
+            doc,
+            metadata_only: self.metadata_only,
+        })
+    }
+
+    /// Returns the bytes of the Info dictionary `field` string as stored in
+    /// the document, before any text decoding.
+    pub fn get_metadata_field_raw(&self, field: &PdfMetaField) -> Option<Vec<u8>> {
+        self.info_dict()?
+            .get(field.as_bytes())
+            .ok()
+            .and_then(|value| value.as_str().ok())
+            .map(<[u8]>::to_vec)
+    }
+
     fn get_metadata_field(&self, field: &[u8]) -> Option<String> {
+        self.info_dict()?
+            .get(field)
+            .ok()
+            .and_then(|value| value.as_str().ok())
+            .map(decode_text_string)
+    }
+
+    /// Resolves the document Info dictionary referenced by the trailer
+    fn info_dict(&self) -> Option<&Dictionary> {
         let doc = &self.doc;
         let info_ref = doc.trailer.get(PDF_META_INFO_KEY).ok()?;
         let object_id = match info_ref {
-            lopdf::Object::Reference(id) => *id,
+            Object::Reference(id) => *id,
             _ => return None,
         };
 
@@ -56,11 +442,276 @@ impl Pdf {
         }
 
         let info_obj = doc.get_object(object_id).ok()?;
-        let dict = info_obj.as_dict().ok()?;
+        info_obj.as_dict().ok()
+    }
+}
 
-        dict.get(field)
-            .ok()
-            .and_then(|value| value.as_str().ok())
-            .map(|bytes| String::from_utf8_lossy(bytes).to_string())
+/// Resolves the Info dictionary of `doc`, creating it when the trailer
+/// doesn't reference one.
+fn info_dict_mut(doc: &mut Document) -> Result<&mut Dictionary> {
+    let object_id = match doc.trailer.get(PDF_META_INFO_KEY) {
+        Ok(Object::Reference(id)) if doc.objects.contains_key(id) => *id,
+        _ => {
+            let id = doc.add_object(Dictionary::new());
+            doc.trailer.set(PDF_META_INFO_KEY, Object::Reference(id));
+            id
+        }
+    };
+
+    Ok(doc.get_object_mut(object_id)?.as_dict_mut()?)
+}
+
+/// Renders an Info dictionary value, decoding strings and falling back to
+/// the debug representation for other objects (e.g. names).
+fn object_to_string(object: &Object) -> String {
+    match object {
+        Object::String(bytes, _) => decode_text_string(bytes),
+        other => format!("{:?}", other),
+    }
+}
+
+/// Characters of PDFDocEncoding differing from Latin-1, from `0x18` to `0x1F`
+const PDF_DOC_ENCODING_18: [char; 8] = ['˘', 'ˇ', 'ˆ', '˙', '˝', '˛', '˚', '˜'];
+
+/// Characters of PDFDocEncoding differing from Latin-1, from `0x80` to `0xA0`
+const PDF_DOC_ENCODING_80: [char; 33] = [
+    '•', '†', '‡', '…', '—', '–', 'ƒ', '⁄', '‹', '›', '−', '‰', '„', '“', '”', '‘', '’', '‚', '™',
+    'ﬁ', 'ﬂ', 'Ł', 'Œ', 'Š', 'Ÿ', 'Ž', 'ı', 'ł', 'œ', 'š', 'ž', '\u{FFFD}', '€',
+];
+
+/// Decodes a PDF text string: UTF-16BE when it starts with the `FE FF` byte
+/// order mark, UTF-8 when it starts with `EF BB BF` or is valid UTF-8, and
+/// PDFDocEncoding otherwise.
+fn decode_text_string(bytes: &[u8]) -> String {
+    if let Some(utf16) = bytes.strip_prefix(&[0xFE, 0xFF]) {
+        let units = utf16
+            .chunks(2)
+            .map(|pair| u16::from_be_bytes([pair[0], *pair.get(1).unwrap_or(&0)]))
+            .collect::<Vec<u16>>();
+
+        return String::from_utf16_lossy(&units);
+    }
+
+    let bytes = bytes.strip_prefix(&[0xEF, 0xBB, 0xBF]).unwrap_or(bytes);
+
+    if let Ok(text) = std::str::from_utf8(bytes) {
+        return text.to_string();
+    }
+
+    bytes
+        .iter()
+        .map(|byte| match byte {
+            0x18..=0x1F => PDF_DOC_ENCODING_18[usize::from(byte - 0x18)],
+            0x80..=0xA0 => PDF_DOC_ENCODING_80[usize::from(byte - 0x80)],
+            _ => char::from(*byte),
+        })
+        .collect()
+}
+
+/// Encodes `value` as a PDF text string, using UTF-16BE with a byte order
+/// mark when it can't be represented as ASCII.
+pub(crate) fn encode_text_string(value: &str) -> Object {
+    if value.is_ascii() {
+        return Object::string_literal(value);
+    }
+
+    let mut bytes = vec![0xFE, 0xFF];
+    bytes.extend(value.encode_utf16().flat_map(|unit| unit.to_be_bytes()));
+
+    Object::String(bytes, StringFormat::Hexadecimal)
+}
+
+/// Object filter for [`Pdf::open_metadata_only`], which drops every stream
+/// but object streams, cross-reference streams and XMP metadata.
+fn keep_metadata_object(object_id: ObjectId, object: &mut Object) -> Option<(ObjectId, Object)> {
+    if let Object::Stream(stream) = object
+        && ![&b"ObjStm"[..], b"XRef", b"Metadata"]
+            .iter()
+            .any(|kind| stream.dict.has_type(kind))
+    {
+        return None;
+    }
+
+    // lopdf keeps using `object` after filtering, so it can't be moved out
+    Some((object_id, object.clone()))
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs::remove_file;
+    use std::process;
+
+    use lopdf::{Document, Object, Stream, dictionary};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn sorts_all_metadata_known_fields_first() {
+        let mut doc = Document::with_version("1.5");
+        let info_id = doc.add_object(dictionary! {
+            "Trapped" => Object::Name(b"False".to_vec()),
+            "Producer" => Object::string_literal("pdfTeX"),
+            "PTEX.Fullbanner" => Object::string_literal("This is pdfTeX"),
+            "Title" => Object::string_literal("Notes"),
+        });
+        doc.trailer.set("Info", Object::Reference(info_id));
+
+        let pdf = Pdf {
+            path: None,
+            doc,
+            metadata_only: false,
+        };
+        let keys = pdf
+            .all_metadata()
+            .into_iter()
+            .map(|(key, _)| key)
+            .collect::<Vec<String>>();
+
+        assert_eq!(
+            keys,
+            vec!["Title", "Producer", "PTEX.Fullbanner", "Trapped"]
+        );
+        assert_eq!(pdf.all_metadata(), pdf.all_metadata());
+    }
+
+    #[tokio::test]
+    async fn counts_pages() {
+        let mut doc = Document::with_version("1.5");
+        let pages_id = doc.new_object_id();
+        let kids = (0..3)
+            .map(|_| {
+                Object::Reference(doc.add_object(dictionary! {
+                    "Type" => "Page",
+                    "Parent" => pages_id,
+                }))
+            })
+            .collect::<Vec<Object>>();
+        doc.objects.insert(
+            pages_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Kids" => kids,
+                "Count" => 3,
+            }),
+        );
+        let catalog_id = doc.add_object(dictionary! {
+            "Type" => "Catalog",
+            "Pages" => pages_id,
+        });
+        doc.trailer.set("Root", catalog_id);
+
+        let pdf = Pdf {
+            path: None,
+            doc,
+            metadata_only: false,
+        };
+
+        assert_eq!(pdf.page_count(), 3);
+    }
+
+    #[tokio::test]
+    async fn decodes_utf16_metadata() -> Result<()> {
+        let mut doc = Document::with_version("1.5");
+        let info_id = doc.add_object(dictionary! {
+            "Title" => encode_text_string("Résumé Title"),
+            "Author" => Object::string_literal(b"Fran\xe7ois".to_vec()),
+        });
+        doc.trailer.set("Info", Object::Reference(info_id));
+
+        let pdf = Pdf {
+            path: None,
+            doc,
+            metadata_only: false,
+        };
+        let metadata = pdf.metadata()?;
+
+        assert_eq!(metadata.title.as_deref(), Some("Résumé Title"));
+        assert_eq!(metadata.author.as_deref(), Some("François"));
+
+        Ok(())
+    }
+
+    /// Sums the bytes of every stream held in memory
+    fn stream_bytes(pdf: &Pdf) -> usize {
+        pdf.doc
+            .objects
+            .values()
+            .filter_map(|object| object.as_stream().ok())
+            .map(|stream| stream.content.len())
+            .sum()
+    }
+
+    #[tokio::test]
+    async fn reads_metadata_without_loading_page_contents() -> Result<()> {
+        const PAGES: usize = 100;
+        const PAGE_SIZE: usize = 16 * 1024;
+
+        let mut doc = Document::with_version("1.5");
+        let pages_id = doc.new_object_id();
+        let kids = (0..PAGES)
+            .map(|page| {
+                let text = format!("BT /F1 12 Tf 72 712 Td (Page {}) Tj ET\n", page);
+                let contents_id = doc.add_object(Stream::new(
+                    dictionary! {},
+                    text.repeat(PAGE_SIZE / text.len()).into_bytes(),
+                ));
+
+                Object::Reference(doc.add_object(dictionary! {
+                    "Type" => "Page",
+                    "Parent" => pages_id,
+                    "Contents" => contents_id,
+                }))
+            })
+            .collect::<Vec<Object>>();
+        doc.objects.insert(
+            pages_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Kids" => kids,
+                "Count" => PAGES as i64,
+            }),
+        );
+        let metadata_id = doc.add_object(Stream::new(
+            dictionary! { "Type" => "Metadata", "Subtype" => "XML" },
+            b"<x:xmpmeta xmlns:x=\"adobe:ns:meta/\"/>".to_vec(),
+        ));
+        let catalog_id = doc.add_object(dictionary! {
+            "Type" => "Catalog",
+            "Pages" => pages_id,
+            "Metadata" => metadata_id,
+        });
+        let info_id = doc.add_object(dictionary! {
+            "Title" => Object::string_literal("Large"),
+            "Author" => Object::string_literal("Jane Doe"),
+        });
+        doc.trailer.set("Root", catalog_id);
+        doc.trailer.set("Info", info_id);
+
+        let path = std::env::temp_dir().join(format!("bookworm-large-{}.pdf", process::id()));
+        doc.save(&path)?;
+
+        let full = Pdf::open(&path)?;
+        let mut metadata_only = Pdf::open_metadata_only(&path)?;
+
+        assert_eq!(
+            serde_json::to_value(metadata_only.metadata()?)?,
+            serde_json::to_value(full.metadata()?)?
+        );
+        assert_eq!(metadata_only.page_count(), PAGES);
+        assert_eq!(full.page_count(), PAGES);
+        assert!(metadata_only.has_xmp_metadata());
+        assert!(full.has_xmp_metadata());
+        // Only the XMP and cross-reference streams are kept, which together
+        // are smaller than the contents of a single page
+        assert!(stream_bytes(&full) > PAGES * (PAGE_SIZE - 64));
+        assert!(stream_bytes(&metadata_only) < PAGE_SIZE);
+
+        let copy = path.with_extension("copy.pdf");
+        assert!(metadata_only.save(&copy).is_err());
+        assert!(!copy.exists());
+
+        remove_file(path)?;
+
+        Ok(())
     }
 }
@@ -0,0 +1,127 @@
+use anyhow::Result;
+use lopdf::Object;
+use xml::reader::{EventReader, XmlEvent};
+
+use crate::pdf::{PDF_CATALOG_METADATA_KEY, Pdf};
+
+const DC_NAMESPACE: &str = "http://purl.org/dc/elements/1.1/";
+
+impl Pdf {
+    /// Returns the XMP packet of the `/Metadata` stream referenced by the
+    /// document catalog, decoded as XML text.
+    pub fn xmp_metadata(&self) -> Result<Option<String>> {
+        let Ok(Object::Reference(id)) = self.doc.catalog()?.get(PDF_CATALOG_METADATA_KEY) else {
+            return Ok(None);
+        };
+
+        let stream = self.doc.get_object(*id)?.as_stream()?;
+        let content = stream.get_plain_content()?;
+
+        Ok(Some(String::from_utf8_lossy(&content).to_string()))
+    }
+
+    /// Returns the `dc:title` of the XMP metadata, preferring the
+    /// `x-default` language alternative.
+    pub fn xmp_title(&self) -> Result<Option<String>> {
+        match self.xmp_metadata()? {
+            Some(xmp) => parse_xmp_title(&xmp),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Finds the `dc:title` `rdf:Alt` entries of an XMP packet and picks the
+/// `x-default` one, or the first one when there is no default.
+fn parse_xmp_title(xmp: &str) -> Result<Option<String>> {
+    let xml_reader = EventReader::from_str(xmp);
+    let mut in_title = false;
+    let mut current_lang: Option<String> = None;
+    let mut in_li = false;
+    let mut titles: Vec<(Option<String>, String)> = Vec::new();
+
+    for event in xml_reader {
+        match event? {
+            XmlEvent::StartElement {
+                name, attributes, ..
+            } => match name.local_name.as_str() {
+                "title" if name.namespace.as_deref() == Some(DC_NAMESPACE) => in_title = true,
+                "li" if in_title => {
+                    in_li = true;
+                    current_lang = attributes
+                        .into_iter()
+                        .find(|attr| {
+                            attr.name.local_name == "lang"
+                                && attr.name.prefix.as_deref() == Some("xml")
+                        })
+                        .map(|attr| attr.value);
+                }
+                _ => {}
+            },
+            XmlEvent::EndElement { name } => match name.local_name.as_str() {
+                "title" if name.namespace.as_deref() == Some(DC_NAMESPACE) => in_title = false,
+                "li" => in_li = false,
+                _ => {}
+            },
+            XmlEvent::Characters(text) if in_li => titles.push((current_lang.take(), text)),
+            _ => {}
+        }
+    }
+
+    let default_title = titles
+        .iter()
+        .position(|(lang, _)| lang.as_deref() == Some("x-default"))
+        .unwrap_or(0);
+
+    Ok(titles
+        .into_iter()
+        .nth(default_title)
+        .map(|(_, title)| title))
+}
+
+#[cfg(test)]
+mod test {
+    use anyhow::Result;
+    use lopdf::{Document, Object, Stream, dictionary};
+
+    use crate::pdf::Pdf;
+
+    const XMP: &str = r#"<?xpacket begin="" id="W5M0MpCehiHzreSzNTczkc9d"?>
+<x:xmpmeta xmlns:x="adobe:ns:meta/">
+  <rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+    <rdf:Description rdf:about="" xmlns:dc="http://purl.org/dc/elements/1.1/">
+      <dc:title>
+        <rdf:Alt>
+          <rdf:li xml:lang="fr">Le Titre</rdf:li>
+          <rdf:li xml:lang="x-default">The Title</rdf:li>
+        </rdf:Alt>
+      </dc:title>
+    </rdf:Description>
+  </rdf:RDF>
+</x:xmpmeta>
+<?xpacket end="w"?>"#;
+
+    #[tokio::test]
+    async fn reads_xmp_title() -> Result<()> {
+        let mut doc = Document::with_version("1.5");
+        let metadata_id = doc.add_object(Stream::new(
+            dictionary! { "Type" => "Metadata", "Subtype" => "XML" },
+            XMP.as_bytes().to_vec(),
+        ));
+        let catalog_id = doc.add_object(dictionary! {
+            "Type" => "Catalog",
+            "Metadata" => metadata_id,
+        });
+        doc.trailer.set("Root", Object::Reference(catalog_id));
+
+        let pdf = Pdf {
+            path: None,
+            doc,
+            metadata_only: false,
+        };
+
+        assert_eq!(pdf.xmp_metadata()?.as_deref(), Some(XMP));
+        assert_eq!(pdf.xmp_title()?.as_deref(), Some("The Title"));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,157 @@
+use std::fs::{File, remove_file, rename};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use anyhow::Result;
+
+/// Compound extension Kobo readers recognise Kobo-enhanced EPUBs by
+const KEPUB_EXTENSION: &str = ".kepub.epub";
+
+/// Returns `path` when nothing exists there yet, otherwise the first free
+/// sibling named `stem (N).ext`, counting from 2. The `.kepub.epub`
+/// extension is kept whole, so `Book.kepub.epub` becomes
+/// `Book (2).kepub.epub`.
+pub fn available_path(path: &Path) -> PathBuf {
+    if !path.exists() {
+        return path.to_path_buf();
+    }
+
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let kepub_stem = file_name
+        .len()
+        .checked_sub(KEPUB_EXTENSION.len())
+        .filter(|&end| end > 0 && file_name.is_char_boundary(end))
+        .filter(|&end| file_name[end..].eq_ignore_ascii_case(KEPUB_EXTENSION));
+    let (stem, extension) = match kepub_stem {
+        Some(end) => (file_name[..end].to_string(), file_name[end..].to_string()),
+        None => (
+            path.file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            path.extension()
+                .map(|ext| format!(".{}", ext.to_string_lossy()))
+                .unwrap_or_default(),
+        ),
+    };
+
+    (2..)
+        .map(|n| path.with_file_name(format!("{} ({}){}", stem, n, extension)))
+        .find(|candidate| !candidate.exists())
+        .unwrap_or_else(|| path.to_path_buf())
+}
+
+/// Replaces the characters file names can't hold on every platform, path
+/// separators included, with dashes
+pub fn sanitize_file_name(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '-',
+            c if c.is_control() => '-',
+            c => c,
+        })
+        .collect()
+}
+
+/// Writes `contents` to a hidden sibling of `path` first, then renames it
+/// over `path`, so a failure mid-write (a crash or a full disk) never leaves
+/// a truncated file behind.
+pub fn write_replacing(path: &Path, contents: &[u8]) -> Result<()> {
+    replace_with(path, |temp_path| {
+        let mut file = File::create(temp_path)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+        Ok(())
+    })
+}
+
+/// Replaces `path` with the file `write` creates at the provided path, a
+/// hidden sibling of `path` suffixed with the process id, so concurrent
+/// writers never share it. The sibling is renamed over `path` once `write`
+/// succeeds and removed otherwise.
+pub fn replace_with<F>(path: &Path, write: F) -> Result<()>
+where
+    F: FnOnce(&Path) -> Result<()>,
+{
+    let file_name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| anyhow::anyhow!("Failed to get file name"))?;
+    let temp_path = path.with_file_name(format!(".{}.{}.tmp", file_name, process::id()));
+
+    if let Err(err) = write(&temp_path).and_then(|_| Ok(rename(&temp_path, path)?)) {
+        let _ = remove_file(&temp_path);
+        return Err(err);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs::{create_dir_all, read, read_dir, remove_dir_all, write};
+
+    use anyhow::Result;
+
+    use super::{available_path, sanitize_file_name, write_replacing};
+
+    #[test]
+    fn numbers_colliding_paths() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("bookworm-fs-{}", std::process::id()));
+        create_dir_all(&dir)?;
+
+        let path = dir.join("Dune - Frank Herbert.epub");
+        assert_eq!(available_path(&path), path);
+
+        write(&path, "")?;
+        assert_eq!(
+            available_path(&path),
+            dir.join("Dune - Frank Herbert (2).epub")
+        );
+
+        write(dir.join("Dune - Frank Herbert (2).epub"), "")?;
+        assert_eq!(
+            available_path(&path),
+            dir.join("Dune - Frank Herbert (3).epub")
+        );
+
+        let kepub = dir.join("Dune.kepub.epub");
+        write(&kepub, "")?;
+        assert_eq!(available_path(&kepub), dir.join("Dune (2).kepub.epub"));
+
+        remove_dir_all(dir)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn sanitizes_file_names() {
+        assert_eq!(
+            sanitize_file_name("AC/DC: Who? <\"Me\"> *|\\\t"),
+            "AC-DC- Who- --Me-- ----"
+        );
+        assert_eq!(sanitize_file_name("Dune"), "Dune");
+    }
+
+    #[test]
+    fn replaces_files_through_a_temporary_sibling() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("bookworm-replace-{}", std::process::id()));
+        create_dir_all(&dir)?;
+
+        let path = dir.join("book.epub");
+        write(&path, "before")?;
+        write_replacing(&path, b"after")?;
+
+        assert_eq!(read(&path)?, b"after");
+        assert_eq!(read_dir(&dir)?.count(), 1);
+        assert!(write_replacing(&dir.join("missing/book.epub"), b"after").is_err());
+
+        remove_dir_all(dir)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,55 @@
+/// Resolves `href`, relative to the file at `base`, into a path relative to
+/// the same root as `base`. `.` and `..` segments are collapsed and fragments
+/// are kept.
+pub fn resolve_relative(base: &str, href: &str) -> String {
+    let mut segments: Vec<&str> = base.split('/').collect();
+
+    // Drop the base file name to keep its directory
+    segments.pop();
+
+    for segment in href.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            segment => segments.push(segment),
+        }
+    }
+
+    segments.join("/")
+}
+
+/// Resolves `href`, an URL relative to the archive file at `base`, into a
+/// path in the archive. The fragment is dropped and the `%XX` escapes are
+/// decoded, e.g. `My%20Chapter.xhtml`.
+pub fn resolve_archive_path(base: &str, href: &str) -> String {
+    let href = href.split(['#', '?']).next().unwrap_or_default();
+
+    percent_decode(&resolve_relative(base, href))
+}
+
+/// Decodes the `%XX` escapes of an URL path, e.g. `My%20Chapter.xhtml`.
+/// Invalid escapes are kept as is.
+pub fn percent_decode(path: &str) -> String {
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+
+    while index < bytes.len() {
+        if bytes[index] == b'%'
+            && let Some(byte) = bytes
+                .get(index + 1..index + 3)
+                .and_then(|hex| std::str::from_utf8(hex).ok())
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        {
+            decoded.push(byte);
+            index += 3;
+        } else {
+            decoded.push(bytes[index]);
+            index += 1;
+        }
+    }
+
+    String::from_utf8_lossy(&decoded).to_string()
+}
@@ -1 +1,3 @@
+pub mod fs;
+pub mod href;
 pub mod zip;
@@ -1,11 +1,16 @@
-use std::fs::File;
-use std::io::Read;
+use std::io::{Read, Seek};
+use std::path::{Path, PathBuf};
 
-use anyhow::{Result, bail};
+use anyhow::{Result, anyhow, bail};
 use zip::ZipArchive;
+use zip::read::ZipFile;
 
-pub fn get_file_bytes(zip: &mut ZipArchive<File>, path: &str) -> Result<Vec<u8>> {
-    let mut file = zip.by_name(path)?;
+/// Opens the file at `path` in the archive for streaming reads.
+pub fn get_file_reader<'a, R: Read + Seek>(
+    zip: &'a mut ZipArchive<R>,
+    path: &str,
+) -> Result<ZipFile<'a, R>> {
+    let file = zip.by_name(path)?;
 
     if !file.is_file() {
         bail!(
@@ -14,8 +19,96 @@ pub fn get_file_bytes(zip: &mut ZipArchive<File>, path: &str) -> Result<Vec<u8>>
         );
     }
 
+    Ok(file)
+}
+
+pub fn get_file_bytes<R: Read + Seek>(zip: &mut ZipArchive<R>, path: &str) -> Result<Vec<u8>> {
+    let mut file = get_file_reader(zip, path)?;
     let mut buffer = Vec::new();
     file.read_to_end(&mut buffer)?;
 
     Ok(buffer)
 }
+
+/// Joins the name of an archive entry to `base`, the directory it is written
+/// into. `.` components are dropped and `..` ones go up a level, so only the
+/// names that stay within `base` are accepted.
+///
+/// Fails on absolute names, names with a drive prefix (`C:`) or a NUL byte,
+/// and names that climb above `base` (zip-slip). Both `/` and `\` are
+/// treated as separators.
+pub fn safe_join(base: &Path, entry_name: &str) -> Result<PathBuf> {
+    let escapes = || {
+        anyhow!(
+            "Refusing to extract entry '{}' outside of the output directory",
+            entry_name
+        )
+    };
+
+    if entry_name.starts_with(['/', '\\']) || entry_name.contains('\0') {
+        return Err(escapes());
+    }
+
+    let mut components = Vec::new();
+
+    for component in entry_name.split(['/', '\\']) {
+        match component {
+            "" | "." => {}
+            ".." => {
+                components.pop().ok_or_else(escapes)?;
+            }
+            // Drive prefixes, which OCF forbids in file names anyway
+            component if component.contains(':') => return Err(escapes()),
+            component => components.push(component),
+        }
+    }
+
+    Ok(components
+        .into_iter()
+        .fold(base.to_path_buf(), |path, component| path.join(component)))
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use anyhow::Result;
+
+    use super::safe_join;
+
+    #[test]
+    fn joins_entry_names_within_the_base() -> Result<()> {
+        let base = Path::new("/tmp/book");
+
+        assert_eq!(
+            safe_join(base, "OEBPS/Text/ch1.xhtml")?,
+            base.join("OEBPS").join("Text").join("ch1.xhtml")
+        );
+        assert_eq!(
+            safe_join(base, "OEBPS/./Images/../Text\\ch1.xhtml")?,
+            base.join("OEBPS").join("Text").join("ch1.xhtml")
+        );
+        assert_eq!(safe_join(base, "META-INF/")?, base.join("META-INF"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_malicious_entry_names() {
+        let base = Path::new("/tmp/book");
+
+        for name in [
+            "../evil.txt",
+            "OEBPS/../../evil.txt",
+            "..\\evil.txt",
+            "OEBPS\\..\\..\\evil.txt",
+            "/etc/passwd",
+            "\\Windows\\evil.txt",
+            "C:\\Windows\\evil.txt",
+            "C:evil.txt",
+            "OEBPS/evil\0.txt",
+        ] {
+            assert!(safe_join(base, name).is_err(), "accepted '{}'", name);
+        }
+    }
+}